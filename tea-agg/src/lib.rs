@@ -1,6 +1,7 @@
 mod vec_valid;
 
 use tea_core::prelude::*;
+use tea_special::{norm_pdf, norm_ppf};
 pub use vec_valid::*;
 
 #[derive(Default, Clone, Copy)]
@@ -11,6 +12,57 @@ pub enum PercentileOfMethod {
     Strict,
 }
 
+/// Enum representing different methods for estimating the value at risk and the expected
+/// shortfall of returns.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum VarMethod {
+    /// Use the empirical distribution of the returns (default).
+    #[default]
+    Historical,
+    /// Use a normal distribution fitted to the mean and the standard deviation of the returns.
+    Gaussian,
+    /// Use the normal quantile adjusted for the skewness and the excess kurtosis of the
+    /// returns by the Cornish-Fisher expansion.
+    CornishFisher,
+}
+
+/// The number of quantiles averaged by the Cornish-Fisher expected shortfall.
+const ES_STEPS: usize = 1000;
+
+/// Collects the valid values of the iterator as f64.
+fn valid_values<T: IsNone, I: IntoIterator<Item = T>>(iter: I) -> Vec<f64>
+where
+    T::Inner: Number,
+{
+    iter.into_iter()
+        .filter_map(|v| v.to_opt().map(|v| v.f64()))
+        .collect()
+}
+
+/// Returns the quantile of the standardized returns at probability `p`, the normal
+/// quantile is adjusted by the Cornish-Fisher expansion if `method` is `CornishFisher`.
+fn var_z(p: f64, method: VarMethod, skew: f64, kurt: f64) -> f64 {
+    let z = norm_ppf(p);
+    match method {
+        VarMethod::CornishFisher => {
+            z + (z * z - 1.) * skew / 6. + (z.powi(3) - 3. * z) * kurt / 24.
+                - (2. * z.powi(3) - 5. * z) * skew * skew / 36.
+        },
+        _ => z,
+    }
+}
+
+/// Collects the valid values of the iterator sorted in ascending order.
+fn sorted_valid<T: IsNone, I: IntoIterator<Item = T>>(iter: I) -> Vec<T::Inner>
+where
+    T::Inner: PartialOrd,
+{
+    let mut out: Vec<_> = iter.into_iter().filter_map(IsNone::to_opt).collect();
+    // valid values are never NaN, so all of them are comparable
+    <[_]>::sort_unstable_by(&mut out, |a, b| a.partial_cmp(b).unwrap());
+    out
+}
+
 /// Extension trait providing additional aggregation methods for iterables with potentially invalid (None) values.
 pub trait AggValidExt<T: IsNone>: IntoIterator<Item = T> + Sized {
     /// Computes the sum of valid values filtered by a mask, along with the count of valid elements.
@@ -138,6 +190,524 @@ pub trait AggValidExt<T: IsNone>: IntoIterator<Item = T> + Sized {
         res
     }
 
+    /// Computes the most frequent valid value of the data.
+    ///
+    /// # Returns
+    ///
+    /// The most frequent value, the smallest of them if several values share the highest
+    /// frequency. Returns None if there is no valid value.
+    fn vmode(self) -> T
+    where
+        T::Inner: PartialOrd,
+    {
+        let sorted = sorted_valid(self);
+        let mut mode: Option<(usize, usize)> = None; // (start, count) of the longest run
+        let mut start = 0;
+        for i in 1..=sorted.len() {
+            if i == sorted.len() || sorted[i] != sorted[start] {
+                if mode.is_none_or(|(_, count)| i - start > count) {
+                    mode = Some((start, i - start));
+                }
+                start = i;
+            }
+        }
+        match mode {
+            Some((start, _)) => T::from_inner(sorted.into_iter().nth(start).unwrap()),
+            None => T::none(),
+        }
+    }
+
+    /// Computes the number of distinct valid values of the data.
+    ///
+    /// # Returns
+    ///
+    /// The number of distinct values, None values are not counted.
+    fn vnunique(self) -> usize
+    where
+        T::Inner: PartialOrd,
+    {
+        let mut sorted = sorted_valid(self);
+        sorted.dedup();
+        sorted.len()
+    }
+
+    /// Computes the weighted mean of the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The non-negative weight of each element, elements whose value or
+    ///   weight is None are ignored.
+    /// * `min_periods` - The minimum number of valid elements required to compute the mean.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the weighted mean of valid elements, NaN if the
+    /// number of valid elements is less than `min_periods` or the sum of the weights is zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the weights is negative.
+    fn vwmean<U, I>(self, weights: I, min_periods: usize) -> TResult<f64>
+    where
+        I: IntoIterator<Item = U>,
+        U: IsNone,
+        U::Inner: Number,
+        T::Inner: Number,
+    {
+        let (mut n, mut sum_w, mut sum_wx) = (0, 0., 0.);
+        for (v, w) in self.into_iter().zip(weights) {
+            if let (Some(v), Some(w)) = (v.to_opt(), w.to_opt()) {
+                let w = w.f64();
+                tensure!(w >= 0., "weights must be non-negative, find {}", w);
+                n += 1;
+                sum_w += w;
+                sum_wx += w * v.f64();
+            }
+        }
+        if n < min_periods || sum_w == 0. {
+            Ok(f64::NAN)
+        } else {
+            Ok(sum_wx / sum_w)
+        }
+    }
+
+    /// Computes the weighted variance of the data.
+    ///
+    /// The weights are treated as reliability weights and the variance is unbiased, so the
+    /// result equals the result of `vvar` if all of the weights are equal.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The non-negative weight of each element, elements whose value or
+    ///   weight is None are ignored.
+    /// * `min_periods` - The minimum number of valid elements required to compute the variance.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the weighted variance of valid elements, NaN if
+    /// the number of valid elements is less than `min_periods`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the weights is negative.
+    fn vwvar<U, I>(self, weights: I, min_periods: usize) -> TResult<f64>
+    where
+        I: IntoIterator<Item = U>,
+        U: IsNone,
+        U::Inner: Number,
+        T::Inner: Number,
+    {
+        let (mut n, mut v1, mut v2, mut m1, mut m2) = (0, 0., 0., 0., 0.);
+        for (v, w) in self.into_iter().zip(weights) {
+            if let (Some(v), Some(w)) = (v.to_opt(), w.to_opt()) {
+                let (v, w) = (v.f64(), w.f64());
+                tensure!(w >= 0., "weights must be non-negative, find {}", w);
+                n += 1;
+                v1 += w;
+                v2 += w * w;
+                m1 += w * v;
+                m2 += w * v * v;
+            }
+        }
+        if n < min_periods {
+            return Ok(f64::NAN);
+        }
+        m1 /= v1; // E(x)
+        m2 /= v1; // E(x^2)
+        // the rounding error of the subtraction is relative to E(x^2)
+        let tol = EPS * m2;
+        m2 -= m1.powi(2); // biased variance
+        let v1_2 = v1.powi(2);
+        if m2 <= tol {
+            Ok(0.)
+        } else if n >= 2 && v1_2 > v2 {
+            Ok(m2 * v1_2 / (v1_2 - v2))
+        } else {
+            Ok(f64::NAN)
+        }
+    }
+
+    /// Computes the weighted standard deviation of the data.
+    ///
+    /// See [`vwvar`](AggValidExt::vwvar) for the arguments and errors.
+    #[inline]
+    fn vwstd<U, I>(self, weights: I, min_periods: usize) -> TResult<f64>
+    where
+        I: IntoIterator<Item = U>,
+        U: IsNone,
+        U::Inner: Number,
+        T::Inner: Number,
+    {
+        Ok(self.vwvar(weights, min_periods)?.sqrt())
+    }
+
+    /// Computes the weighted quantile of the data.
+    ///
+    /// The weights are rescaled to sum to the number of valid elements and each element
+    /// occupies a block of positions as long as its weight, the element is located at the
+    /// center of its block. The quantile is then found at position `q * (n - 1)` by the
+    /// given method, so the result equals the result of `vquantile` if all of the weights
+    /// are equal.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The non-negative weight of each element, elements whose value or
+    ///   weight is None or whose weight is zero are ignored.
+    /// * `q` - The quantile to calculate, must be between 0 and 1.
+    /// * `method` - The method to use for quantile calculation.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the calculated quantile value, NaN if there is
+    /// no valid element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `q` is not between 0 and 1, if any of the weights is negative or
+    /// if `method` is not one of `Linear`, `Lower`, `Higher` and `MidPoint`.
+    fn vwquantile<U, I>(self, weights: I, q: f64, method: QuantileMethod) -> TResult<f64>
+    where
+        I: IntoIterator<Item = U>,
+        U: IsNone,
+        U::Inner: Number,
+        T::Inner: Number,
+    {
+        tensure!(
+            (0. ..=1.).contains(&q),
+            "q must be between 0 and 1, find {}",
+            q
+        );
+        use QuantileMethod::*;
+        tensure!(
+            matches!(method, Linear | Lower | Higher | MidPoint),
+            "weighted quantile only supports the Linear, Lower, Higher and MidPoint methods"
+        );
+        let mut pairs = Vec::new();
+        for (v, w) in self.into_iter().zip(weights) {
+            if let (Some(v), Some(w)) = (v.to_opt(), w.to_opt()) {
+                let w = w.f64();
+                tensure!(w >= 0., "weights must be non-negative, find {}", w);
+                if w > 0. {
+                    pairs.push((v.f64(), w));
+                }
+            }
+        }
+        let n = pairs.len();
+        if n == 0 {
+            return Ok(f64::NAN);
+        }
+        <[_]>::sort_unstable_by(&mut pairs, |a, b| a.0.partial_cmp(&b.0).unwrap());
+        let scale = n.f64() / pairs.iter().fold(0., |acc, p| acc + p.1);
+        let mut cum_w = 0.;
+        let positions: Vec<f64> = pairs
+            .iter()
+            .map(|(_, w)| {
+                let w = w * scale;
+                let pos = cum_w + (w - 1.) / 2.;
+                cum_w += w;
+                pos
+            })
+            .collect();
+        let h = q * (n - 1).f64();
+        // index of the first element located after `h`
+        let j = positions.partition_point(|&pos| pos <= h);
+        if j == 0 {
+            return Ok(pairs[0].0);
+        } else if j == n || positions[j - 1] == h {
+            return Ok(pairs[j - 1].0);
+        }
+        let (vi, vj) = (pairs[j - 1].0, pairs[j].0);
+        match method {
+            Linear => {
+                let fraction = (h - positions[j - 1]) / (positions[j] - positions[j - 1]);
+                Ok(vi + (vj - vi) * fraction)
+            },
+            Lower => Ok(vi),
+            Higher => Ok(vj),
+            MidPoint => Ok((vi + vj) / 2.),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Computes the Sharpe ratio of the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `risk_free` - The risk free rate of one period.
+    /// * `annualize` - The number of periods in a year, e.g. `252.` for daily returns.
+    /// * `min_periods` - The minimum number of valid elements required to compute the ratio.
+    ///
+    /// # Returns
+    ///
+    /// The annualized mean excess return divided by the annualized standard deviation of
+    /// the returns, or NaN if the number of valid elements is less than `min_periods` or
+    /// the standard deviation is zero.
+    fn vsharpe(self, risk_free: f64, annualize: f64, min_periods: usize) -> f64
+    where
+        T::Inner: Number,
+    {
+        let (mean, var) = self.vmean_var(min_periods);
+        if var > 0. {
+            (mean - risk_free) / var.sqrt() * annualize.sqrt()
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Computes the Sortino ratio of the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target return of one period.
+    /// * `annualize` - The number of periods in a year, e.g. `252.` for daily returns.
+    /// * `min_periods` - The minimum number of valid elements required to compute the ratio.
+    ///
+    /// # Returns
+    ///
+    /// The annualized mean return in excess of `target` divided by the annualized downside
+    /// deviation `sqrt(mean(min(r - target, 0)^2))`, or NaN if the number of valid elements
+    /// is less than `min_periods` or there is no return below `target`.
+    fn vsortino(self, target: f64, annualize: f64, min_periods: usize) -> f64
+    where
+        T::Inner: Number,
+    {
+        let returns = valid_values(self);
+        let n = returns.len();
+        if n < min_periods || n == 0 {
+            return f64::NAN;
+        }
+        let (excess, downside) = returns.iter().fold((0., 0.), |(excess, downside), r| {
+            let d = r - target;
+            (excess + d, downside + d.min(0.).powi(2))
+        });
+        if downside > 0. {
+            excess / n.f64() / (downside / n.f64()).sqrt() * annualize.sqrt()
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Computes the maximum drawdown of the equity curve compounded from the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_periods` - The minimum number of valid elements required to compute the
+    ///   drawdown.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the maximum drawdown as a positive fraction of the peak equity and the
+    /// indices of the first and the last return of the drawdown, i.e. the first return
+    /// after the peak and the return at the trough. The indices are None if there is no
+    /// drawdown. The drawdown is NaN if the number of valid elements is less than
+    /// `min_periods` or there is no valid element.
+    fn vmax_drawdown(self, min_periods: usize) -> (f64, Option<(usize, usize)>)
+    where
+        T::Inner: Number,
+    {
+        let (mut n, mut equity, mut peak) = (0, 1., 1.);
+        let (mut max_dd, mut range, mut start) = (0., None, None);
+        for (i, r) in self.into_iter().enumerate() {
+            let Some(r) = r.to_opt() else { continue };
+            n += 1;
+            equity *= 1. + r.f64();
+            if equity >= peak {
+                peak = equity;
+                start = None;
+            } else {
+                let start = *start.get_or_insert(i);
+                let dd = 1. - equity / peak;
+                if dd > max_dd {
+                    max_dd = dd;
+                    range = Some((start, i));
+                }
+            }
+        }
+        if n < min_periods || n == 0 {
+            (f64::NAN, None)
+        } else {
+            (max_dd, range)
+        }
+    }
+
+    /// Computes the Calmar ratio of the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `annualize` - The number of periods in a year, e.g. `252.` for daily returns.
+    /// * `min_periods` - The minimum number of valid elements required to compute the ratio.
+    ///
+    /// # Returns
+    ///
+    /// The annualized compound return divided by the maximum drawdown, or NaN if the
+    /// number of valid elements is less than `min_periods` or there is no drawdown.
+    fn vcalmar(self, annualize: f64, min_periods: usize) -> f64
+    where
+        T::Inner: Number,
+    {
+        let returns = valid_values(self);
+        let n = returns.len();
+        if n < min_periods || n == 0 {
+            return f64::NAN;
+        }
+        let (max_dd, _) = returns.titer().vmax_drawdown(0);
+        let total = returns.iter().fold(1., |acc, r| acc * (1. + r));
+        if max_dd > 0. {
+            (total.powf(annualize / n.f64()) - 1.) / max_dd
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Computes the Omega ratio of the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The threshold return of one period.
+    /// * `min_periods` - The minimum number of valid elements required to compute the ratio.
+    ///
+    /// # Returns
+    ///
+    /// The sum of the gains above `threshold` divided by the sum of the losses below it,
+    /// or NaN if the number of valid elements is less than `min_periods` or there is no
+    /// loss.
+    fn vomega(self, threshold: f64, min_periods: usize) -> f64
+    where
+        T::Inner: Number,
+    {
+        let (mut n, mut gain, mut loss) = (0, 0., 0.);
+        self.into_iter().for_each(|r| {
+            if let Some(r) = r.to_opt() {
+                let d = r.f64() - threshold;
+                n += 1;
+                if d > 0. {
+                    gain += d;
+                } else {
+                    loss -= d;
+                }
+            }
+        });
+        if n < min_periods || loss == 0. {
+            f64::NAN
+        } else {
+            gain / loss
+        }
+    }
+
+    /// Computes the tail ratio of the returns, the absolute value of the 0.95 quantile
+    /// divided by the absolute value of the 0.05 quantile.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_periods` - The minimum number of valid elements required to compute the ratio.
+    ///
+    /// # Returns
+    ///
+    /// The tail ratio, or NaN if the number of valid elements is less than `min_periods`.
+    fn vtail_ratio(self, min_periods: usize) -> f64
+    where
+        T::Inner: Number,
+    {
+        let returns = valid_values(self);
+        if returns.len() < min_periods {
+            return f64::NAN;
+        }
+        let q = returns
+            .vquantiles(&[0.95, 0.05], QuantileMethod::Linear)
+            .unwrap();
+        (q[0] / q[1]).abs()
+    }
+
+    /// Computes the value at risk of the returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The confidence level, e.g. `0.95`.
+    /// * `method` - The method used to estimate the `1 - level` quantile of the returns,
+    ///   see [`VarMethod`].
+    /// * `min_periods` - The minimum number of valid elements required to compute the value
+    ///   at risk.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the value at risk as a positive loss, i.e. the
+    /// negative of the `1 - level` quantile, or NaN if the number of valid elements is
+    /// less than `min_periods`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `level` is not between 0 and 1.
+    fn vvalue_at_risk(self, level: f64, method: VarMethod, min_periods: usize) -> TResult<f64>
+    where
+        T::Inner: Number,
+    {
+        tensure!(
+            level > 0. && level < 1.,
+            "level must be between 0 and 1, find {}",
+            level
+        );
+        let returns = valid_values(self);
+        if returns.len() < min_periods {
+            return Ok(f64::NAN);
+        }
+        if let VarMethod::Historical = method {
+            return Ok(-returns.vquantile(1. - level, QuantileMethod::Linear)?);
+        }
+        let (mean, var) = returns.titer().vmean_var(0);
+        let (skew, kurt) = (returns.titer().vskew(0), returns.titer().vkurt(0));
+        Ok(-(mean + var.sqrt() * var_z(1. - level, method, skew, kurt)))
+    }
+
+    /// Computes the expected shortfall (conditional value at risk) of the returns.
+    ///
+    /// The historical method averages the returns at or below the `1 - level` quantile,
+    /// the Gaussian method uses the closed form of the normal distribution and the
+    /// Cornish-Fisher method averages the adjusted quantiles over the tail.
+    ///
+    /// See [`vvalue_at_risk`](AggValidExt::vvalue_at_risk) for the arguments and the
+    /// errors.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the expected shortfall as a positive loss, or
+    /// NaN if the number of valid elements is less than `min_periods`.
+    fn vexpected_shortfall(self, level: f64, method: VarMethod, min_periods: usize) -> TResult<f64>
+    where
+        T::Inner: Number,
+    {
+        tensure!(
+            level > 0. && level < 1.,
+            "level must be between 0 and 1, find {}",
+            level
+        );
+        let returns = valid_values(self);
+        if returns.len() < min_periods {
+            return Ok(f64::NAN);
+        }
+        let alpha = 1. - level;
+        let (mean, var) = returns.titer().vmean_var(0);
+        let std = var.sqrt();
+        Ok(match method {
+            VarMethod::Historical => {
+                let var = returns.vquantile(alpha, QuantileMethod::Linear)?;
+                let (n, sum) = returns
+                    .titer()
+                    .filter(|r| *r <= var)
+                    .vfold_n(0., |acc, r| acc + r);
+                -sum / n.f64()
+            },
+            VarMethod::Gaussian => -(mean - std * norm_pdf(norm_ppf(alpha)) / alpha),
+            VarMethod::CornishFisher => {
+                let (skew, kurt) = (returns.titer().vskew(0), returns.titer().vkurt(0));
+                let z = (0..ES_STEPS).fold(0., |acc, i| {
+                    let p = alpha * (i.f64() + 0.5) / ES_STEPS.f64();
+                    acc + var_z(p, method, skew, kurt)
+                }) / ES_STEPS.f64();
+                -(mean + std * z)
+            },
+        })
+    }
+
     /// Computes the percentile rank of a given score relative to a list of scores.
     ///
     /// # Arguments
@@ -218,4 +788,147 @@ mod tests {
             4. / 7.
         )
     }
+
+    #[test]
+    fn test_mode_nunique() {
+        let a = vec![Some(3), Some(2), None, Some(3), Some(2), Some(1)];
+        assert_eq!(a.clone().vmode(), Some(2));
+        assert_eq!(a.vnunique(), 3);
+        let a = vec![1., f64::NAN, 0.5, 1., f64::NAN];
+        assert_eq!(a.clone().vmode(), 1.);
+        assert_eq!(a.vnunique(), 2);
+        assert!(Vec::<f64>::new().vmode().is_nan());
+        assert_eq!(vec![None::<i32>].vnunique(), 0);
+    }
+
+    #[test]
+    fn test_weighted() -> TResult<()> {
+        let a = vec![Some(1.), Some(2.), Some(3.), None];
+        let w = vec![1., 2., 3., 4.];
+        assert_eq!(a.clone().vwmean(w.clone(), 1)?, 14. / 6.);
+        assert!(a.clone().vwmean(w.clone(), 4)?.is_nan());
+        assert!((a.clone().vwvar(w.clone(), 1)? - 10. / 11.).abs() < EPS);
+        assert!((a.clone().vwstd(w.clone(), 1)? - (10. / 11_f64).sqrt()).abs() < EPS);
+        assert!(a.clone().vwvar(w, 4)?.is_nan());
+        // equal weights give the unweighted statistics
+        let w = vec![2.; 4];
+        assert!((a.clone().vwvar(w.clone(), 1)? - a.clone().vvar(1)).abs() < EPS);
+        assert_eq!(a.clone().vwmean(w, 1)?, 2.);
+        assert_eq!(vec![1.].vwvar(vec![1.], 1)?, vec![1.].vvar(1));
+        assert!(vec![1., 2.].vwmean(vec![0., 0.], 1)?.is_nan());
+        assert!(vec![1., 2.].vwmean(vec![1., -1.], 1).is_err());
+        assert!(vec![1., 2., 3.].vwmean(vec![1., -1., 2.], 1).is_err());
+        assert!(vec![1., 2., 3.].vwvar(vec![1., -1., 2.], 1).is_err());
+        assert!(vec![1., 2., 3.].vwstd(vec![1., -1., 2.], 1).is_err());
+        // a small variance is not treated as zero, a constant series has zero variance
+        let a = vec![1e-6, 2e-6, 3e-6];
+        assert!((a.clone().vwvar(vec![1.; 3], 1)? / 1e-12 - 1.).abs() < 1e-9);
+        assert_eq!(vec![1e8 + 0.1; 3].vwvar(vec![1., 2., 3.], 1)?, 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wquantile() -> TResult<()> {
+        use QuantileMethod::*;
+        let a: Vec<i32> = (1..=10).collect();
+        let w = vec![0.5; 10];
+        for q in [0., 0.22, 0.25, 0.5, 0.75, 0.78, 1.] {
+            for method in [Linear, Lower, Higher, MidPoint] {
+                let expect = a.vquantile(q, method)?;
+                assert!((a.clone().vwquantile(w.clone(), q, method)? - expect).abs() < EPS);
+            }
+        }
+        let a = vec![1., 2., 3., f64::NAN];
+        let w = vec![Some(1.), Some(1.), Some(2.), Some(5.)];
+        assert!((a.clone().vwquantile(w.clone(), 0.5, Linear)? - 7. / 3.).abs() < EPS);
+        assert_eq!(a.clone().vwquantile(w.clone(), 0.5, Lower)?, 2.);
+        assert_eq!(a.clone().vwquantile(w.clone(), 0.5, Higher)?, 3.);
+        assert_eq!(a.clone().vwquantile(w.clone(), 0.5, MidPoint)?, 2.5);
+        // a dominant weight pulls the median towards its value
+        let median = vec![1., 2.].vwquantile(vec![1., 100.], 0.5, Linear)?;
+        assert!((median - (1. + 200. / 202.)).abs() < EPS);
+        assert_eq!(vec![1., 2.].vwquantile(vec![0., 1.], 0., Linear)?, 2.);
+        assert!(
+            Vec::<f64>::new()
+                .vwquantile(Vec::<f64>::new(), 0.5, Linear)?
+                .is_nan()
+        );
+        assert!(a.clone().vwquantile(w, 1.5, Linear).is_err());
+        assert!(vec![1.].vwquantile(vec![-1.], 0.5, Linear).is_err());
+        assert!(vec![1.].vwquantile(vec![1.], 0.5, Hazen).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_performance() -> TResult<()> {
+        let r = vec![
+            Some(0.1),
+            Some(-0.05),
+            None,
+            Some(0.02),
+            Some(-0.1),
+            Some(0.03),
+            Some(0.04),
+            Some(-0.02),
+            Some(0.05),
+        ];
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-10;
+        assert!(close(
+            r.titer().vsharpe(0.001, 252., 1),
+            1.956_181_381_721_575
+        ));
+        assert!(close(
+            r.titer().vsortino(0., 252., 1),
+            3.459_062_919_212_336
+        ));
+        let (mdd, range) = r.titer().vmax_drawdown(1);
+        assert!(close(mdd, 0.1279));
+        assert_eq!(range, Some((1, 4)));
+        assert!(close(r.titer().vcalmar(252., 1), 37.559_264_531_721_9));
+        assert!(close(r.titer().vomega(0., 1), 1.411_764_705_882_353));
+        assert!(close(r.titer().vtail_ratio(1), 1.));
+        use VarMethod::*;
+        assert!(close(
+            r.titer().vvalue_at_risk(0.95, Historical, 1)?,
+            0.0825
+        ));
+        assert!(close(
+            r.titer().vvalue_at_risk(0.95, Gaussian, 1)?,
+            0.094_697_525_956_238_73
+        ));
+        assert!(close(
+            r.titer().vvalue_at_risk(0.95, CornishFisher, 1)?,
+            0.103_126_258_148_721_53
+        ));
+        assert!(close(
+            r.titer().vexpected_shortfall(0.95, Historical, 1)?,
+            0.1
+        ));
+        assert!(close(
+            r.titer().vexpected_shortfall(0.95, Gaussian, 1)?,
+            0.120_977_370_994_322_43
+        ));
+        assert!(close(
+            r.titer().vexpected_shortfall(0.95, CornishFisher, 1)?,
+            0.135_059_991_509_541_93
+        ));
+        // min_periods is not met
+        assert!(r.titer().vsharpe(0., 252., 10).is_nan());
+        assert!(r.titer().vsortino(0., 252., 10).is_nan());
+        assert!(r.titer().vmax_drawdown(10).0.is_nan());
+        assert!(r.titer().vcalmar(252., 10).is_nan());
+        assert!(r.titer().vomega(0., 10).is_nan());
+        assert!(r.titer().vtail_ratio(10).is_nan());
+        assert!(r.titer().vvalue_at_risk(0.95, Gaussian, 10)?.is_nan());
+        assert!(
+            r.titer()
+                .vexpected_shortfall(0.95, Historical, 10)?
+                .is_nan()
+        );
+        assert!(r.titer().vvalue_at_risk(1., Historical, 1).is_err());
+        // no drawdown
+        assert_eq!(vec![0.1, 0., 0.2].vmax_drawdown(1), (0., None));
+        assert!(vec![0.1, 0.2].vcalmar(252., 1).is_nan());
+        Ok(())
+    }
 }
@@ -2,7 +2,7 @@ use tea_core::prelude::*;
 /// Enum representing different methods for calculating quantiles.
 #[derive(Copy, Clone)]
 pub enum QuantileMethod {
-    /// Linear interpolation between closest ranks.
+    /// Linear interpolation between closest ranks (Hyndman-Fan type 7).
     Linear,
     /// Use the lower of the two nearest ranks.
     Lower,
@@ -10,6 +10,242 @@ pub enum QuantileMethod {
     Higher,
     /// Use the average of the two nearest ranks.
     MidPoint,
+    /// Inverse of the empirical distribution function (Hyndman-Fan type 1).
+    InvertedCdf,
+    /// Inverse of the empirical distribution function, averaged at discontinuities
+    /// (Hyndman-Fan type 2).
+    AveragedInvertedCdf,
+    /// The nearest even order statistic (Hyndman-Fan type 3).
+    ClosestObservation,
+    /// Linear interpolation of the empirical distribution function (Hyndman-Fan type 4).
+    InterpolatedInvertedCdf,
+    /// Piecewise linear function whose knots are the midpoints of the steps of the
+    /// empirical distribution function (Hyndman-Fan type 5).
+    Hazen,
+    /// Linear interpolation with `p[k] = E[F(x[k])]` (Hyndman-Fan type 6).
+    Weibull,
+    /// Linear interpolation with approximately median unbiased quantiles regardless of
+    /// the distribution (Hyndman-Fan type 8).
+    MedianUnbiased,
+    /// Linear interpolation with approximately unbiased quantiles for normal data
+    /// (Hyndman-Fan type 9).
+    NormalUnbiased,
+}
+
+/// Returns the zero based positions of the two order statistics needed by the quantile `q`
+/// of `n` values and the interpolation weight of the second one.
+fn quantile_position(q: f64, n: usize, method: QuantileMethod) -> (usize, usize, f64) {
+    use QuantileMethod::*;
+    let n_f64 = n.f64();
+    // clamps a one based order statistic
+    let clamp = |k: f64| (k.max(1.).min(n_f64) - 1.).usize();
+    match method {
+        Lower | Higher | MidPoint => {
+            let h = (n - 1).f64() * q;
+            let (i, j) = (h.floor().usize(), h.ceil().usize());
+            match method {
+                Lower => (i, i, 0.),
+                Higher => (j, j, 0.),
+                _ => (i, j, if i == j { 0. } else { 0.5 }),
+            }
+        },
+        InvertedCdf => {
+            let i = clamp((n_f64 * q).ceil());
+            (i, i, 0.)
+        },
+        AveragedInvertedCdf => {
+            let h = n_f64 * q;
+            if h.fract() == 0. && h >= 1. && h < n_f64 {
+                (clamp(h), clamp(h + 1.), 0.5)
+            } else {
+                let i = clamp(h.ceil());
+                (i, i, 0.)
+            }
+        },
+        ClosestObservation => {
+            let h = n_f64 * q - 0.5;
+            let j = h.floor();
+            let i = if h == j && j % 2. == 0. {
+                clamp(j)
+            } else {
+                clamp(j + 1.)
+            };
+            (i, i, 0.)
+        },
+        _ => {
+            // one based position of the continuous methods
+            let h = match method {
+                InterpolatedInvertedCdf => n_f64 * q,
+                Hazen => n_f64 * q + 0.5,
+                Weibull => (n_f64 + 1.) * q,
+                MedianUnbiased => (n_f64 + 1. / 3.) * q + 1. / 3.,
+                NormalUnbiased => (n_f64 + 0.25) * q + 3. / 8.,
+                _ => (n - 1).f64() * q + 1.,
+            };
+            if h < 1. {
+                (0, 0, 0.)
+            } else if h >= n_f64 {
+                (n - 1, n - 1, 0.)
+            } else {
+                let k = h.floor();
+                (k.usize() - 1, k.usize(), h - k)
+            }
+        },
+    }
+}
+
+/// Reorders a slice without NaN so that the values at the sorted positions `ks` are the
+/// order statistics at those positions, `offset` is the position of the start of the slice.
+fn multi_select(slc: &mut [f64], ks: &[usize], offset: usize) {
+    if ks.is_empty() {
+        return;
+    }
+    let mid = ks.len() / 2;
+    let k = ks[mid] - offset;
+    let (left, _, right) = slc.select_nth_unstable_by(k, |a, b| a.partial_cmp(b).unwrap());
+    multi_select(left, &ks[..mid], offset);
+    multi_select(right, &ks[mid + 1..], offset + k + 1);
+}
+
+/// Collects the valid values of the vector as f64.
+fn valid_f64<T: IsNone, V: Vec1View<T> + ?Sized>(vec: &V) -> Vec<f64>
+where
+    T::Inner: Number,
+{
+    vec.titer()
+        .filter_map(|v| v.to_opt().map(|v| v.f64()))
+        .collect()
+}
+
+/// Returns the k-th smallest value (zero based) of a slice without NaN, the slice is
+/// partially reordered.
+#[inline]
+fn select_kth(slc: &mut [f64], k: usize) -> f64 {
+    *slc.select_nth_unstable_by(k, |a, b| a.partial_cmp(b).unwrap())
+        .1
+}
+
+/// Returns the median of a slice without NaN using selection, NaN if the slice is empty.
+fn select_median(slc: &mut [f64]) -> f64 {
+    let n = slc.len();
+    if n == 0 {
+        return f64::NAN;
+    }
+    let upper = select_kth(slc, n / 2);
+    if n % 2 == 1 {
+        upper
+    } else {
+        // the lower middle value is the largest value before the upper one
+        let lower = slc[..n / 2]
+            .iter()
+            .fold(f64::NEG_INFINITY, |acc, &v| acc.max(v));
+        (lower + upper) / 2.
+    }
+}
+
+/// Partitions a slice without NaN so that the `k` smallest values come first and the `k`
+/// largest values come last, the middle values are returned along with the smallest and
+/// the largest of them.
+fn select_trimmed(slc: &mut [f64], k: usize) -> (&[f64], f64, f64) {
+    let n = slc.len();
+    let lo = select_kth(slc, k);
+    let hi = select_kth(&mut slc[k..], n - 2 * k - 1);
+    (&slc[k..n - k], lo, hi)
+}
+
+/// Returns the weighted median of `(value, weight)` pairs without NaN, which is the
+/// smallest value whose cumulative weight is at least half of the total weight. The
+/// pairs are reordered and the total weight must be positive.
+fn weighted_median(mut items: &mut [(f64, usize)]) -> f64 {
+    let mut need = Iterator::sum::<usize>(items.iter().map(|v| v.1)).div_ceil(2);
+    loop {
+        let mid = items.len() / 2;
+        let (left, pivot, right) =
+            items.select_nth_unstable_by(mid, |a, b| a.0.partial_cmp(&b.0).unwrap());
+        let weight = Iterator::sum::<usize>(left.iter().map(|v| v.1));
+        if weight >= need {
+            items = left;
+        } else if weight + pivot.1 >= need {
+            return pivot.0;
+        } else {
+            need -= weight + pivot.1;
+            items = right;
+        }
+    }
+}
+
+/// Returns the k-th smallest value (zero based) of a matrix whose row `i` is `f(i, j)`
+/// for `j` in `lo(i)..n`, each row must be sorted in ascending order.
+///
+/// This is the selection algorithm of Johnson and Mizoguchi used by Croux and Rousseeuw
+/// for the Qn estimator and by Monahan for the Hodges-Lehmann estimator: the candidates
+/// of each row are narrowed by the weighted median of the row medians until at most `n`
+/// are left. The positions of a threshold move monotonically between rows for the
+/// matrices used here, so each step takes `O(n)` time and the total time is
+/// `O(n log n)` with `O(n)` memory.
+fn select_kth_sorted_rows<L, F>(n: usize, lo: L, f: F, k: usize) -> f64
+where
+    L: Fn(usize) -> usize,
+    F: Fn(usize, usize) -> f64,
+{
+    let lo: Vec<usize> = (0..n).map(lo).collect();
+    // the candidates of row `i` are in `left[i]..right[i]`
+    let mut left = lo.clone();
+    let mut right = vec![n; n];
+    // the end of the values of each row which are less than (or equal to) a pivot
+    let ends = |pivot: f64, equal: bool| -> Vec<usize> {
+        let below = |v: f64| if equal { v <= pivot } else { v < pivot };
+        let mut end = 0;
+        lo.iter()
+            .enumerate()
+            .map(|(i, &lo)| {
+                end = end.max(lo);
+                while end > lo && !below(f(i, end - 1)) {
+                    end -= 1;
+                }
+                while end < n && below(f(i, end)) {
+                    end += 1;
+                }
+                end
+            })
+            .collect()
+    };
+    // the total length of the ranges `b[i]..a[i]`
+    let gap =
+        |a: &[usize], b: &[usize]| -> usize { Iterator::sum(a.iter().zip(b).map(|(a, b)| a - b)) };
+    let mut medians = Vec::with_capacity(n);
+    loop {
+        let count = gap(&right, &left);
+        let below = gap(&left, &lo);
+        if count <= n {
+            let mut rest: Vec<f64> = (0..n)
+                .flat_map(|i| (left[i]..right[i]).map(move |j| (i, j)))
+                .map(|(i, j)| f(i, j))
+                .collect();
+            return select_kth(&mut rest, k - below);
+        }
+        medians.clear();
+        medians.extend(
+            (0..n)
+                .filter(|&i| right[i] > left[i])
+                .map(|i| (f(i, (left[i] + right[i] - 1) / 2), right[i] - left[i])),
+        );
+        let pivot = weighted_median(&mut medians);
+        let less = ends(pivot, false);
+        let n_less = gap(&less, &lo);
+        if k < n_less {
+            right.iter_mut().zip(less).for_each(|(r, e)| *r = e.min(*r));
+            continue;
+        }
+        let less_equal = ends(pivot, true);
+        let n_less_equal = gap(&less_equal, &lo);
+        if k < n_less_equal {
+            return pivot;
+        }
+        left.iter_mut()
+            .zip(less_equal)
+            .for_each(|(l, e)| *l = e.max(*l));
+    }
 }
 
 /// Extension trait providing additional aggregation methods for vectors with potentially invalid (None) values.
@@ -39,6 +275,9 @@ pub trait VecAggValidExt<T: IsNone>: Vec1View<T> {
             q
         );
         use QuantileMethod::*;
+        if !matches!(method, Linear | Lower | Higher | MidPoint) {
+            return Ok(self.vquantiles(&[q], method)?[0]);
+        }
         let mut out_c: Vec<_> = self.titer().collect_trusted_vec1(); // clone the array
         let slc = out_c.try_as_slice_mut().unwrap();
         let n = self.titer().count_valid();
@@ -92,9 +331,65 @@ pub trait VecAggValidExt<T: IsNone>: Vec1View<T> {
             Lower => Ok(vi),                // i
             Higher => Ok(vj),               // j
             MidPoint => Ok((vi + vj) / 2.), // (i + j) / 2.
+            _ => unreachable!(),
         }
     }
 
+    /// Calculate several quantiles of the vector at once, ignoring NaN or None values.
+    ///
+    /// The valid values are copied into a single buffer and all of the order statistics
+    /// needed by the quantiles are selected in one recursive pass, which is faster than
+    /// calling [`vquantile`](VecAggValidExt::vquantile) for each quantile.
+    ///
+    /// # Arguments
+    ///
+    /// * `qs` - The quantiles to calculate, each must be between 0 and 1.
+    /// * `method` - The method to use for quantile calculation.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<Vec<f64>>` containing the quantile values in the order of `qs`,
+    /// NaN if there is no valid value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of `qs` is not between 0 and 1.
+    fn vquantiles(&self, qs: &[f64], method: QuantileMethod) -> TResult<Vec<f64>>
+    where
+        T::Inner: Number,
+    {
+        for &q in qs {
+            tensure!(
+                (0. ..=1.).contains(&q),
+                "q must be between 0 and 1, find {}",
+                q
+            );
+        }
+        let mut vals = valid_f64(self);
+        let n = vals.len();
+        if n == 0 {
+            return Ok(vec![f64::NAN; qs.len()]);
+        }
+        let positions: Vec<_> = qs
+            .iter()
+            .map(|&q| quantile_position(q, n, method))
+            .collect();
+        let mut ks: Vec<usize> = positions.iter().flat_map(|&(i, j, _)| [i, j]).collect();
+        <[_]>::sort_unstable(&mut ks);
+        ks.dedup();
+        multi_select(&mut vals, &ks, 0);
+        Ok(positions
+            .into_iter()
+            .map(|(i, j, fraction)| {
+                if fraction == 0. {
+                    vals[i]
+                } else {
+                    vals[i] + (vals[j] - vals[i]) * fraction
+                }
+            })
+            .collect())
+    }
+
     /// Calculate the median of the vector, ignoring NaN or None values.
     ///
     /// # Returns
@@ -108,14 +403,221 @@ pub trait VecAggValidExt<T: IsNone>: Vec1View<T> {
     {
         self.vquantile(0.5, QuantileMethod::Linear).unwrap()
     }
+
+    /// Calculate the median absolute deviation of the vector, ignoring NaN or None values.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The constant the deviation is multiplied by, `1.4826` makes the result
+    ///   a consistent estimator of the standard deviation for normal data.
+    ///
+    /// # Returns
+    ///
+    /// The scaled median absolute deviation, NaN if there is no valid value.
+    fn vmad(&self, scale: f64) -> f64
+    where
+        T::Inner: Number,
+    {
+        let mut vals = valid_f64(self);
+        let median = select_median(&mut vals);
+        vals.iter_mut().for_each(|v| *v = (*v - median).abs());
+        select_median(&mut vals) * scale
+    }
+
+    /// Calculate the interquartile range of the vector, ignoring NaN or None values.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method to use for quantile calculation.
+    ///
+    /// # Returns
+    ///
+    /// The difference between the 0.75 and the 0.25 quantiles.
+    #[inline]
+    fn viqr(&self, method: QuantileMethod) -> f64
+    where
+        T::Inner: Number,
+    {
+        let qs = self.vquantiles(&[0.25, 0.75], method).unwrap();
+        qs[1] - qs[0]
+    }
+
+    /// Calculate the trimmed mean of the vector, ignoring NaN or None values.
+    ///
+    /// # Arguments
+    ///
+    /// * `prop` - The proportion of values cut off from each end, `floor(prop * n)` values
+    ///   are removed from each side.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the mean of the remaining values, NaN if there
+    /// is no valid value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `prop` is not in `[0, 0.5)`.
+    fn vtrimmed_mean(&self, prop: f64) -> TResult<f64>
+    where
+        T::Inner: Number,
+    {
+        tensure!(
+            (0. ..0.5).contains(&prop),
+            "prop must be in [0, 0.5), find {}",
+            prop
+        );
+        let mut vals = valid_f64(self);
+        if vals.is_empty() {
+            return Ok(f64::NAN);
+        }
+        let k = (prop * vals.len().f64()).floor().usize();
+        let (middle, _, _) = select_trimmed(&mut vals, k);
+        Ok(middle.iter().fold(0., |acc, v| acc + v) / middle.len().f64())
+    }
+
+    /// Calculate the winsorized mean of the vector, ignoring NaN or None values.
+    ///
+    /// # Arguments
+    ///
+    /// * `prop` - The proportion of values clipped at each end, the `floor(prop * n)`
+    ///   smallest and largest values are replaced by the nearest remaining value.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TResult<f64>` containing the mean of the winsorized values, NaN if there
+    /// is no valid value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `prop` is not in `[0, 0.5)`.
+    fn vwinsorized_mean(&self, prop: f64) -> TResult<f64>
+    where
+        T::Inner: Number,
+    {
+        tensure!(
+            (0. ..0.5).contains(&prop),
+            "prop must be in [0, 0.5), find {}",
+            prop
+        );
+        let mut vals = valid_f64(self);
+        let n = vals.len();
+        if n == 0 {
+            return Ok(f64::NAN);
+        }
+        let k = (prop * n.f64()).floor().usize();
+        let (middle, lo, hi) = select_trimmed(&mut vals, k);
+        let sum = middle.iter().fold(0., |acc, v| acc + v) + k.f64() * (lo + hi);
+        Ok(sum / n.f64())
+    }
+
+    /// Calculate the Hodges-Lehmann estimator of the location of the vector, ignoring NaN
+    /// or None values.
+    ///
+    /// The Walsh averages are not materialized, the median of them is selected in
+    /// `O(n log n)` time with `O(n)` memory using the algorithm of Monahan.
+    ///
+    /// # Returns
+    ///
+    /// The median of the averages of all pairs of values (each value paired with itself
+    /// included), NaN if there is no valid value.
+    fn vhodges_lehmann(&self) -> f64
+    where
+        T::Inner: Number,
+    {
+        let mut vals = valid_f64(self);
+        let n = vals.len();
+        if n == 0 {
+            return f64::NAN;
+        }
+        <[_]>::sort_unstable_by(&mut vals, |a, b| a.partial_cmp(b).unwrap());
+        // row `i` of the Walsh averages is `(x[i] + x[j]) / 2` for `j >= i`
+        let walsh = |k| select_kth_sorted_rows(n, |i| i, |i, j| (vals[i] + vals[j]) / 2., k);
+        let total = n * (n + 1) / 2;
+        if total % 2 == 1 {
+            walsh(total / 2)
+        } else {
+            (walsh(total / 2 - 1) + walsh(total / 2)) / 2.
+        }
+    }
+
+    /// Calculate the Qn scale estimator of Rousseeuw and Croux, ignoring NaN or None values.
+    ///
+    /// The estimator is the `k`-th smallest of the pairwise distances `|x_i - x_j|, i < j`,
+    /// where `k = h * (h - 1) / 2` and `h = n / 2 + 1`. The distances are not materialized,
+    /// the estimator is selected in `O(n log n)` time with `O(n)` memory using the
+    /// algorithm of Croux and Rousseeuw.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The constant the estimator is multiplied by, `2.2219` makes the result a
+    ///   consistent estimator of the standard deviation for normal data (without small
+    ///   sample correction).
+    ///
+    /// # Returns
+    ///
+    /// The scaled estimator, NaN if there are less than 2 valid values.
+    fn vqn(&self, scale: f64) -> f64
+    where
+        T::Inner: Number,
+    {
+        let mut vals = valid_f64(self);
+        let n = vals.len();
+        if n < 2 {
+            return f64::NAN;
+        }
+        <[_]>::sort_unstable_by(&mut vals, |a, b| a.partial_cmp(b).unwrap());
+        let h = n / 2 + 1;
+        // row `i` of the distances is `x[j] - x[i]` for `j > i`
+        select_kth_sorted_rows(n, |i| i + 1, |i, j| vals[j] - vals[i], h * (h - 1) / 2 - 1) * scale
+    }
+
+    /// Calculate the Sn scale estimator of Rousseeuw and Croux, ignoring NaN or None values.
+    ///
+    /// The estimator is `lomed_i himed_j |x_i - x_j|`, where the high median is the
+    /// `(n / 2 + 1)`-th smallest value and the low median is the `((n + 1) / 2)`-th
+    /// smallest value.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The constant the estimator is multiplied by, `1.1926` makes the result a
+    ///   consistent estimator of the standard deviation for normal data (without small
+    ///   sample correction).
+    ///
+    /// # Returns
+    ///
+    /// The scaled estimator, NaN if there is no valid value.
+    fn vsn(&self, scale: f64) -> f64
+    where
+        T::Inner: Number,
+    {
+        let vals = valid_f64(self);
+        let n = vals.len();
+        if n == 0 {
+            return f64::NAN;
+        }
+        let mut dist = vec![0.; n];
+        let mut himeds: Vec<f64> = vals
+            .iter()
+            .map(|a| {
+                dist.iter_mut()
+                    .zip(&vals)
+                    .for_each(|(d, b)| *d = (a - b).abs());
+                select_kth(&mut dist, n / 2)
+            })
+            .collect();
+        select_kth(&mut himeds, n.div_ceil(2) - 1) * scale
+    }
 }
 impl<V: Vec1View<T>, T: IsNone> VecAggValidExt<T> for V {}
 
 #[cfg(test)]
 mod tests {
+    use tea_core::testing::assert_vec1d_equal_numeric;
+
+    use super::*;
+
     #[test]
     fn test_quantile() {
-        use super::*;
         let a = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
         assert_eq!(a.vquantile(0.5, QuantileMethod::Linear).unwrap(), 5.5);
         assert_eq!(a.vquantile(0.5, QuantileMethod::Lower).unwrap(), 5.);
@@ -132,4 +634,125 @@ mod tests {
         assert_eq!(a.vquantile(0.22, QuantileMethod::Linear).unwrap(), 2.98);
         assert_eq!(a.vquantile(0.78, QuantileMethod::Linear).unwrap(), 8.02);
     }
+
+    #[test]
+    fn test_robust() -> TResult<()> {
+        let a = vec![Some(1.), Some(2.), None, Some(3.), Some(4.), Some(100.)];
+        assert_eq!(a.vmad(1.), 1.);
+        assert_eq!(a.vmad(1.4826), 1.4826);
+        assert_eq!(vec![1, 2, 3, 4].vmad(1.), 1.);
+        assert_eq!(a.vtrimmed_mean(0.2)?, 3.);
+        assert_eq!(a.vtrimmed_mean(0.)?, 22.);
+        assert_eq!(a.vwinsorized_mean(0.2)?, 3.);
+        assert!(a.vtrimmed_mean(0.5).is_err());
+        assert!(a.vwinsorized_mean(-0.1).is_err());
+        let b: Vec<i32> = (1..=10).collect();
+        assert_eq!(b.viqr(QuantileMethod::Linear), 4.5);
+        assert_eq!(b.vtrimmed_mean(0.1)?, 5.5);
+        assert_eq!(b.vwinsorized_mean(0.25)?, 5.5);
+        assert_eq!(vec![1., 2., 3.].vhodges_lehmann(), 2.);
+        assert_eq!(vec![1., 2., 10.].vhodges_lehmann(), 3.75);
+        assert_eq!(vec![1, 2, 3, 4, 5].vqn(1.), 1.);
+        assert_eq!(vec![1, 3, 6, 10].vqn(1.), 4.);
+        assert_eq!(vec![1, 2, 3, 4, 5].vsn(1.), 1.);
+        assert_eq!(vec![1, 3, 6, 10].vsn(1.), 4.);
+        assert!(vec![1].vqn(1.).is_nan());
+        let empty: Vec<f64> = vec![];
+        assert!(empty.vmad(1.).is_nan());
+        assert!(empty.vtrimmed_mean(0.1)?.is_nan());
+        assert!(empty.vhodges_lehmann().is_nan());
+        assert!(empty.vsn(1.).is_nan());
+        // compare with the selection from all pairs
+        let mut seed = 7u64;
+        for n in [2, 3, 10, 51, 200] {
+            let c: Vec<f64> = (0..n)
+                .map(|_| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    // few distinct values so that there are many ties
+                    ((seed >> 33) % 37) as f64 / 4.
+                })
+                .collect();
+            let mut walsh = vec![];
+            let mut dist = vec![];
+            for i in 0..n {
+                for j in i..n {
+                    walsh.push((c[i] + c[j]) / 2.);
+                    if j > i {
+                        dist.push((c[i] - c[j]).abs());
+                    }
+                }
+            }
+            assert_eq!(c.vhodges_lehmann(), select_median(&mut walsh));
+            let h = n / 2 + 1;
+            assert_eq!(c.vqn(1.), select_kth(&mut dist, h * (h - 1) / 2 - 1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantiles() -> TResult<()> {
+        use QuantileMethod::*;
+        let a = vec![
+            Some(7.),
+            Some(1.),
+            None,
+            Some(10.),
+            Some(4.),
+            Some(9.),
+            Some(2.),
+            Some(5.),
+            Some(8.),
+            Some(3.),
+            Some(6.),
+        ];
+        let qs = [0., 0.1, 0.22, 0.25, 0.5, 0.75, 0.78, 1.];
+        for method in [Linear, Lower, Higher, MidPoint] {
+            let res = a.vquantiles(&qs, method)?;
+            for (q, v) in qs.iter().zip(res) {
+                assert!((a.vquantile(*q, method)? - v).abs() < EPS);
+            }
+        }
+        // expected values are the results of `quantile(x, qs, type = t)` in R
+        let expect = [
+            (InvertedCdf, [1., 1., 3., 3., 5., 8., 8., 10.]),
+            (AveragedInvertedCdf, [1., 1.5, 3., 3., 5.5, 8., 8., 10.]),
+            (ClosestObservation, [1., 1., 2., 2., 5., 8., 8., 10.]),
+            (
+                InterpolatedInvertedCdf,
+                [1., 1., 2.2, 2.5, 5., 7.5, 7.8, 10.],
+            ),
+            (Hazen, [1., 1.5, 2.7, 3., 5.5, 8., 8.3, 10.]),
+            (Weibull, [1., 1.1, 2.42, 2.75, 5.5, 8.25, 8.58, 10.]),
+            (
+                MedianUnbiased,
+                [
+                    1.,
+                    1.366_666_666_666_667,
+                    2.606_666_666_666_667,
+                    2.916_666_666_666_667,
+                    5.5,
+                    8.083_333_333_333_333,
+                    8.393_333_333_333_333,
+                    10.,
+                ],
+            ),
+            (
+                NormalUnbiased,
+                [1., 1.4, 2.63, 2.9375, 5.5, 8.0625, 8.37, 10.],
+            ),
+        ];
+        for (method, expect) in expect {
+            let res = a.vquantiles(&qs, method)?;
+            assert_vec1d_equal_numeric(&res, &expect.to_vec(), Some(1e-10));
+            assert!((a.vquantile(0.22, method)? - expect[2]).abs() < 1e-10);
+        }
+        assert!(a.vquantiles(&[0.5, 1.1], Linear).is_err());
+        let res = Vec::<f64>::new().vquantiles(&[0.1, 0.9], Hazen)?;
+        assert!(res.len() == 2 && res[0].is_nan() && res[1].is_nan());
+        assert_eq!(
+            vec![3.].vquantiles(&[0., 0.5, 1.], Weibull)?,
+            vec![3., 3., 3.]
+        );
+        Ok(())
+    }
 }
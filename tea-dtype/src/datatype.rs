@@ -266,6 +266,7 @@ impl From<&tea_deps::polars::prelude::DataType> for DataType {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "polars")]
     use super::*;
 
     #[test]
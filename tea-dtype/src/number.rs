@@ -70,6 +70,12 @@ pub trait Number:
     /// Computes the absolute value of the number.
     fn abs(self) -> Self;
 
+    /// Returns true if the type is a floating point type.
+    #[inline(always)]
+    fn is_float() -> bool {
+        false
+    }
+
     /// Computes the ceiling of the number.
     ///
     /// For integer types, this is typically the identity function.
@@ -217,6 +223,11 @@ macro_rules! impl_number {
         $(impl Number for $dtype {
             impl_number!(@ base_impl $dtype, $datatype);
 
+            #[inline(always)]
+            fn is_float() -> bool {
+                true
+            }
+
             #[inline]
             fn ceil(self) -> Self {
                 self.ceil()
@@ -0,0 +1,303 @@
+use tea_core::prelude::*;
+
+/// A scalar operand which is broadcast to the length of the other operand of a binary
+/// operation.
+#[derive(Clone, Copy, Debug)]
+pub struct Scalar<T>(pub T);
+
+/// A trait for the right operand of the binary operations in [`MapValidBinary`], which
+/// is either an iterator of the same length as the left operand or a [`Scalar`].
+pub trait Broadcast<T> {
+    /// The iterator the operand is broadcast to.
+    type Iter: TrustedLen<Item = T>;
+
+    /// Broadcasts the operand to an iterator of length `len`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operand is an iterator whose length is not `len`.
+    fn broadcast(self, len: usize) -> TResult<Self::Iter>;
+}
+
+impl<T: Clone> Broadcast<T> for Scalar<T> {
+    type Iter = std::iter::RepeatN<T>;
+
+    #[inline]
+    fn broadcast(self, len: usize) -> TResult<Self::Iter> {
+        Ok(std::iter::repeat_n(self.0, len))
+    }
+}
+
+impl<T, I: TrustedLen<Item = T>> Broadcast<T> for I {
+    type Iter = I;
+
+    #[inline]
+    fn broadcast(self, len: usize) -> TResult<Self::Iter> {
+        tensure!(
+            TrustedLen::len(&self) == len,
+            lm,
+            len,
+            TrustedLen::len(&self)
+        );
+        Ok(self)
+    }
+}
+
+/// Applies `f` to the pairs of valid values, the result is None if any of the values
+/// is None.
+#[inline]
+fn binary_map<'a, T, I, B, F>(iter: I, other: B, f: F) -> TResult<impl TrustedLen<Item = T> + 'a>
+where
+    T: IsNone + 'a,
+    I: TrustedLen<Item = T> + 'a,
+    B: Broadcast<T> + 'a,
+    F: Fn(T::Inner, T::Inner) -> T::Inner + 'a,
+{
+    let other = other.broadcast(TrustedLen::len(&iter))?;
+    Ok(iter
+        .zip(other)
+        .map(move |(a, b)| match (a.to_opt(), b.to_opt()) {
+            (Some(a), Some(b)) => T::from_inner(f(a, b)),
+            _ => T::none(),
+        }))
+}
+
+/// Trait for elementwise binary arithmetic on trusted length iterators, the right
+/// operand is an iterator of the same length or a [`Scalar`] and the result is None
+/// where any of the operands is None.
+///
+/// A [`Scalar`] can only be the right operand, use the reflected methods
+/// [`vrsub`](MapValidBinary::vrsub) and [`vrdiv`](MapValidBinary::vrdiv) for a scalar
+/// on the left of the non-commutative operations.
+pub trait MapValidBinary<T: IsNone>: TrustedLen<Item = T> + Sized {
+    /// Adds the operands elementwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `other` doesn't match the length of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidBinary, Scalar};
+    ///
+    /// let a = vec![Some(1), None, Some(3)];
+    /// let b = vec![Some(10), Some(20), Some(30)];
+    /// let result: Vec<_> = a.titer().vadd(b.titer()).unwrap().collect();
+    /// assert_eq!(result, vec![Some(11), None, Some(33)]);
+    /// let result: Vec<_> = a.titer().vadd(Scalar(Some(1))).unwrap().collect();
+    /// assert_eq!(result, vec![Some(2), None, Some(4)]);
+    /// ```
+    #[inline]
+    fn vadd<'a, B: Broadcast<T> + 'a>(self, other: B) -> TResult<impl TrustedLen<Item = T> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        binary_map(self, other, |a, b| a + b)
+    }
+
+    /// Subtracts `other` from the iterator elementwise.
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    #[inline]
+    fn vsub<'a, B: Broadcast<T> + 'a>(self, other: B) -> TResult<impl TrustedLen<Item = T> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        binary_map(self, other, |a, b| a - b)
+    }
+
+    /// Subtracts the iterator from `other` elementwise, i.e. the reflected
+    /// [`vsub`](MapValidBinary::vsub).
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidBinary, Scalar};
+    ///
+    /// let a = vec![Some(1), None, Some(3)];
+    /// let result: Vec<_> = a.titer().vrsub(Scalar(Some(10))).unwrap().collect();
+    /// assert_eq!(result, vec![Some(9), None, Some(7)]);
+    /// ```
+    #[inline]
+    fn vrsub<'a, B: Broadcast<T> + 'a>(self, other: B) -> TResult<impl TrustedLen<Item = T> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        binary_map(self, other, |a, b| b - a)
+    }
+
+    /// Multiplies the operands elementwise.
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    #[inline]
+    fn vmul<'a, B: Broadcast<T> + 'a>(self, other: B) -> TResult<impl TrustedLen<Item = T> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        binary_map(self, other, |a, b| a * b)
+    }
+
+    /// Divides the iterator by `other` elementwise, the division is always performed in
+    /// f64 so that integers are not truncated and a zero divisor gives an infinity or
+    /// NaN. None values are NaN in the output.
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidBinary, Scalar};
+    ///
+    /// let a = vec![1, 3, 0];
+    /// let result: Vec<_> = a.titer().vdiv(Scalar(2)).unwrap().collect();
+    /// assert_eq!(result, vec![0.5, 1.5, 0.]);
+    /// ```
+    #[inline]
+    fn vdiv<'a, B: Broadcast<T> + 'a>(self, other: B) -> TResult<impl TrustedLen<Item = f64> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        let other = other.broadcast(TrustedLen::len(&self))?;
+        Ok(self
+            .zip(other)
+            .map(|(a, b)| match (a.to_opt(), b.to_opt()) {
+                (Some(a), Some(b)) => a.f64() / b.f64(),
+                _ => f64::NAN,
+            }))
+    }
+
+    /// Divides `other` by the iterator elementwise, i.e. the reflected
+    /// [`vdiv`](MapValidBinary::vdiv).
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidBinary, Scalar};
+    ///
+    /// let a = vec![1, 4, 0];
+    /// let result: Vec<_> = a.titer().vrdiv(Scalar(2)).unwrap().collect();
+    /// assert_eq!(result, vec![2., 0.5, f64::INFINITY]);
+    /// ```
+    #[inline]
+    fn vrdiv<'a, B: Broadcast<T> + 'a>(self, other: B) -> TResult<impl TrustedLen<Item = f64> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        let other = other.broadcast(TrustedLen::len(&self))?;
+        Ok(self
+            .zip(other)
+            .map(|(a, b)| match (a.to_opt(), b.to_opt()) {
+                (Some(a), Some(b)) => b.f64() / a.f64(),
+                _ => f64::NAN,
+            }))
+    }
+
+    /// Returns the elementwise minimum of the operands.
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    #[inline]
+    fn vmin_with<'a, B: Broadcast<T> + 'a>(
+        self,
+        other: B,
+    ) -> TResult<impl TrustedLen<Item = T> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        binary_map(self, other, Number::min_with)
+    }
+
+    /// Returns the elementwise maximum of the operands.
+    ///
+    /// See [`vadd`](MapValidBinary::vadd) for the errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidBinary, Scalar};
+    ///
+    /// let a = vec![-1., f64::NAN, 2.];
+    /// let result: Vec<_> = a.titer().vmax_with(Scalar(0.)).unwrap().collect();
+    /// assert_eq!(result[0], 0.);
+    /// assert!(result[1].is_nan());
+    /// assert_eq!(result[2], 2.);
+    /// ```
+    #[inline]
+    fn vmax_with<'a, B: Broadcast<T> + 'a>(
+        self,
+        other: B,
+    ) -> TResult<impl TrustedLen<Item = T> + 'a>
+    where
+        T: 'a,
+        T::Inner: Number,
+        Self: 'a,
+    {
+        binary_map(self, other, Number::max_with)
+    }
+}
+
+impl<T: IsNone, I: TrustedLen<Item = T>> MapValidBinary<T> for I {}
+
+#[cfg(test)]
+mod test {
+    use tea_core::testing::assert_vec1d_equal_numeric;
+
+    use super::*;
+
+    #[test]
+    fn test_binary() -> TResult<()> {
+        let nan = f64::NAN;
+        let a = vec![1., nan, 3., -4.];
+        let b = vec![2., 5., nan, 4.];
+        let res: Vec<_> = a.titer().vadd(b.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![3., nan, nan, 0.], None);
+        let res: Vec<_> = a.titer().vsub(b.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![-1., nan, nan, -8.], None);
+        let res: Vec<_> = a.titer().vmul(Scalar(2.))?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![2., nan, 6., -8.], None);
+        let res: Vec<_> = a.titer().vdiv(b.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![0.5, nan, nan, -1.], None);
+        let res: Vec<_> = a.titer().vmin_with(b.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, nan, -4.], None);
+        let res: Vec<_> = a.titer().vmax_with(Scalar(nan))?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, nan, nan], None);
+        // operations can be chained
+        let res: Vec<_> = a.titer().vadd(Scalar(1.))?.vmul(b.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![4., nan, nan, -12.], None);
+        let res: Vec<_> = vec![1, 2].titer().vdiv(vec![0, 4].titer())?.collect();
+        assert_eq!(res, vec![f64::INFINITY, 0.5]);
+        // a scalar on the left of the non-commutative operations
+        let res: Vec<_> = a.titer().vrsub(Scalar(1.))?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![0., nan, -2., 5.], None);
+        let res: Vec<_> = a.titer().vrdiv(Scalar(6.))?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![6., nan, 2., -1.5], None);
+        let res: Vec<_> = a.titer().vrsub(b.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, nan, 8.], None);
+        assert!(a.titer().vadd(b.titer().take(3)).is_err());
+        assert!(a.titer().vrdiv(b.titer().take(3)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::hash::Hash;
+
+#[cfg(feature = "time")]
+use tea_core::prelude::{DateTime, TimeUnitTrait};
+
+/// A trait for values which can be used as the key of a hash map or a hash set.
+///
+/// Floating point values are hashed by their bit pattern, `-0.` and `0.` share the same
+/// key. The key of a None value is never requested, the methods using this trait treat
+/// all of the None values (including NaN) as a single key.
+pub trait HashKey {
+    /// The hashable key type.
+    type Key: Hash + Eq;
+
+    /// Returns the hashable key of the value.
+    fn hash_key(&self) -> Self::Key;
+}
+
+macro_rules! impl_hash_key_float {
+    ($($type: ty => $bits: ty),*) => {
+        $(
+            impl HashKey for $type {
+                type Key = $bits;
+
+                #[inline]
+                fn hash_key(&self) -> Self::Key {
+                    if *self == 0. { 0 } else { self.to_bits() }
+                }
+            }
+        )*
+    };
+}
+
+impl_hash_key_float!(f32 => u32, f64 => u64);
+
+macro_rules! impl_hash_key {
+    ($($type: ty),*) => {
+        $(
+            impl HashKey for $type {
+                type Key = $type;
+
+                #[inline]
+                fn hash_key(&self) -> Self::Key {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_hash_key!(
+    bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, String
+);
+
+impl<'a> HashKey for &'a str {
+    type Key = &'a str;
+
+    #[inline]
+    fn hash_key(&self) -> Self::Key {
+        self
+    }
+}
+
+#[cfg(feature = "time")]
+impl<U: TimeUnitTrait> HashKey for DateTime<U> {
+    type Key = i64;
+
+    #[inline]
+    fn hash_key(&self) -> Self::Key {
+        self.0
+    }
+}
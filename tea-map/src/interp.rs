@@ -0,0 +1,170 @@
+use tea_core::prelude::*;
+
+/// Enum representing different methods for interpolating missing values.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum InterpMethod {
+    /// Linear interpolation between the previous and the next valid values (default).
+    #[default]
+    Linear,
+    /// Use the nearer one of the previous and the next valid values.
+    Nearest,
+    /// Use the previous valid value.
+    Previous,
+    /// Use the next valid value.
+    Next,
+    /// Polynomial interpolation of the given order through the `order + 1` valid values
+    /// nearest to the gap.
+    Polynomial(usize),
+    /// Natural cubic spline through all of the valid values.
+    CubicSpline,
+}
+
+/// Enum representing in which direction consecutive missing values are filled when
+/// interpolating with a limit.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum LimitDirection {
+    /// Fill the missing values after a valid value (default).
+    #[default]
+    Forward,
+    /// Fill the missing values before a valid value.
+    Backward,
+    /// Fill the missing values in both directions.
+    Both,
+}
+
+/// Second derivatives of the natural cubic spline through the points `(xs, ys)`.
+fn natural_spline(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len();
+    let mut m = vec![0.; n];
+    if n < 3 {
+        return m;
+    }
+    // solve the tridiagonal system by the Thomas algorithm
+    let (mut c, mut d) = (vec![0.; n], vec![0.; n]);
+    for i in 1..n - 1 {
+        let (h0, h1) = (xs[i] - xs[i - 1], xs[i + 1] - xs[i]);
+        let rhs = 6. * ((ys[i + 1] - ys[i]) / h1 - (ys[i] - ys[i - 1]) / h0);
+        let denom = 2. * (h0 + h1) - h0 * c[i - 1];
+        c[i] = h1 / denom;
+        d[i] = (rhs - h0 * d[i - 1]) / denom;
+    }
+    for i in (1..n - 1).rev() {
+        m[i] = d[i] - c[i] * m[i + 1];
+    }
+    m
+}
+
+/// Evaluates the spline segment between the points `k` and `k + 1` at `x`.
+#[inline]
+fn spline_eval(xs: &[f64], ys: &[f64], m: &[f64], k: usize, x: f64) -> f64 {
+    let h = xs[k + 1] - xs[k];
+    let (a, b) = (xs[k + 1] - x, x - xs[k]);
+    m[k] * a.powi(3) / (6. * h)
+        + m[k + 1] * b.powi(3) / (6. * h)
+        + (ys[k] / h - m[k] * h / 6.) * a
+        + (ys[k + 1] / h - m[k + 1] * h / 6.) * b
+}
+
+/// Evaluates the Lagrange polynomial through the points `(xs, ys)` at `x`.
+#[inline]
+fn lagrange_eval(xs: &[f64], ys: &[f64], x: f64) -> f64 {
+    (0..xs.len()).fold(0., |acc, i| {
+        let basis = (0..xs.len())
+            .filter(|j| *j != i)
+            .fold(1., |acc, j| acc * (x - xs[j]) / (xs[i] - xs[j]));
+        acc + ys[i] * basis
+    })
+}
+
+/// Interpolates the missing values of `y` at positions `x`, the positions must be
+/// strictly increasing and the missing values at a missing position are not filled.
+///
+/// `within(p, i)` returns whether the element `i` is within the limit of the element
+/// `p` before it. `k` below is the position (in the valid points) of the next valid
+/// point of a gap.
+pub(crate) fn interpolate<F: Fn(usize, usize) -> bool>(
+    x: &[f64],
+    y: &[f64],
+    method: InterpMethod,
+    within: F,
+    limit_direction: LimitDirection,
+) -> Vec<f64> {
+    let n = y.len();
+    let valid: Vec<usize> = (0..n)
+        .filter(|i| y[*i].not_none() && x[*i].not_none())
+        .collect();
+    let mut out = y.to_vec();
+    if valid.is_empty() {
+        return out;
+    }
+    let xs: Vec<f64> = valid.iter().map(|i| x[*i]).collect();
+    let ys: Vec<f64> = valid.iter().map(|i| y[*i]).collect();
+    let spline = matches!(method, InterpMethod::CubicSpline).then(|| natural_spline(&xs, &ys));
+    let mut k = 0;
+    for i in 0..n {
+        if k < valid.len() && valid[k] == i {
+            k += 1;
+            continue;
+        }
+        if x[i].is_none() {
+            continue;
+        }
+        let prev = k.checked_sub(1).map(|j| valid[j]);
+        let next = (k < valid.len()).then(|| valid[k]);
+        let forward = prev.is_some_and(|p| within(p, i));
+        let backward = next.is_some_and(|q| within(i, q));
+        let fill = match limit_direction {
+            LimitDirection::Forward => forward,
+            LimitDirection::Backward => backward,
+            LimitDirection::Both => forward || backward,
+        };
+        if !fill {
+            continue;
+        }
+        let xi = x[i];
+        out[i] = match (prev, next) {
+            (Some(p), Some(q)) => {
+                let (xp, xq) = (x[p], x[q]);
+                match method {
+                    InterpMethod::Linear => {
+                        if xq > xp {
+                            y[p] + (y[q] - y[p]) * (xi - xp) / (xq - xp)
+                        } else {
+                            y[p]
+                        }
+                    },
+                    InterpMethod::Nearest => {
+                        if xi - xp <= xq - xi {
+                            y[p]
+                        } else {
+                            y[q]
+                        }
+                    },
+                    InterpMethod::Previous => y[p],
+                    InterpMethod::Next => y[q],
+                    InterpMethod::Polynomial(order) => {
+                        let size = (order + 1).min(valid.len());
+                        // points before the gap are preferred when size is odd
+                        let start = k.saturating_sub(size.div_ceil(2)).min(valid.len() - size);
+                        lagrange_eval(&xs[start..start + size], &ys[start..start + size], xi)
+                    },
+                    InterpMethod::CubicSpline => {
+                        spline_eval(&xs, &ys, spline.as_ref().unwrap(), k - 1, xi)
+                    },
+                }
+            },
+            // missing values before the first valid value
+            (None, Some(q)) => match method {
+                InterpMethod::Previous => f64::NAN,
+                _ => y[q],
+            },
+            // missing values after the last valid value
+            (Some(p), None) => match method {
+                InterpMethod::Next => f64::NAN,
+                _ => y[p],
+            },
+            (None, None) => unreachable!(),
+        };
+    }
+    out
+}
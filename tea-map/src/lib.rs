@@ -1,9 +1,17 @@
+mod binary;
+mod hash_key;
+mod interp;
 mod valid_iter;
 mod vec_map;
 
+pub use binary::{Broadcast, MapValidBinary, Scalar};
+pub use hash_key::HashKey;
+pub use interp::{InterpMethod, LimitDirection};
 use tea_core::prelude::*;
 pub use valid_iter::{Keep, MapValidBasic};
-pub use vec_map::MapValidVec;
+#[cfg(feature = "agg")]
+pub use vec_map::{Duplicates, QcutBins};
+pub use vec_map::{MapValidVec, NanPlacement, RankMethod, RankNorm, SearchSide};
 /// A trait for basic mapping operations on trusted length iterators.
 ///
 /// This trait provides methods for common operations like absolute value and shifting,
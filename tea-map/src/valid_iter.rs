@@ -1,7 +1,10 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
 use tea_core::prelude::*;
 
+use crate::HashKey;
+
 #[derive(Clone, Debug)]
 pub enum Keep {
     First,
@@ -33,6 +36,228 @@ pub trait MapValidBasic<T: IsNone>: TrustedLen<Item = T> + Sized {
         self.map(|v| v.vabs())
     }
 
+    /// Computes the sign of each element (-1, 0 or 1), ignoring None values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(-3), None, Some(0), Some(2)];
+    /// let result: Vec<_> = v.titer().vsign().collect();
+    /// assert_eq!(result, vec![Some(-1), None, Some(0), Some(1)]);
+    /// ```
+    #[inline]
+    fn vsign(self) -> impl TrustedLen<Item = T>
+    where
+        T::Inner: Number,
+        f64: Cast<T::Inner>,
+    {
+        inner_map(self, |v| {
+            if v > 0. {
+                1.
+            } else if v < 0. {
+                -1.
+            } else {
+                0.
+            }
+        })
+    }
+
+    /// Rounds each element to the given number of decimals, ignoring None values.
+    ///
+    /// A negative `decimals` rounds to the left of the decimal point, halfway cases are
+    /// rounded away from zero. Integers are rounded with integer arithmetic, so they are
+    /// unchanged for a non-negative `decimals` and the results which overflow the type
+    /// saturate at its bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![1.234, f64::NAN, -5.678];
+    /// let result: Vec<_> = v.titer().vround(1).collect();
+    /// assert_eq!(result[0], 1.2);
+    /// assert!(result[1].is_nan());
+    /// assert_eq!(result[2], -5.7);
+    /// let result: Vec<_> = vec![1234, 1250].titer().vround(-2).collect();
+    /// assert_eq!(result, vec![1200, 1300]);
+    /// ```
+    #[inline]
+    fn vround(self, decimals: i32) -> impl TrustedLen<Item = T>
+    where
+        T::Inner: Number,
+        f64: Cast<T::Inner>,
+    {
+        let scale = 10f64.powi(decimals.abs());
+        let is_float = T::Inner::is_float();
+        self.map(move |v| match v.to_opt() {
+            Some(v) if is_float => T::from_inner(round_decimals(v.f64(), decimals, scale).cast()),
+            Some(v) if decimals >= 0 => T::from_inner(v),
+            // every integer is less than half of the scale
+            Some(_) if scale >= T::Inner::max_().f64() => T::from_inner(T::Inner::default()),
+            Some(v) => T::from_inner(round_int_to_step(v, scale.cast(), StepRound::Nearest)),
+            None => T::none(),
+        })
+    }
+
+    /// Rounds each element down to a multiple of `step` (e.g. the tick size), ignoring
+    /// None values.
+    ///
+    /// Integers are rounded with integer arithmetic if `step` is an integer, the results
+    /// which overflow the type saturate at its bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `step` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![10.07, 0.3, -0.01];
+    /// let result: Vec<_> = v.titer().vfloor_to(0.05).unwrap().collect();
+    /// assert_eq!(result, vec![10.05, 0.3, -0.05]);
+    /// ```
+    #[inline]
+    fn vfloor_to(self, step: f64) -> TResult<impl TrustedLen<Item = T>>
+    where
+        T::Inner: Number,
+        f64: Cast<T::Inner>,
+    {
+        step_map(self, step, StepRound::Floor)
+    }
+
+    /// Rounds each element up to a multiple of `step` (e.g. the tick size), ignoring
+    /// None values.
+    ///
+    /// Integers are rounded with integer arithmetic if `step` is an integer, the results
+    /// which overflow the type saturate at its bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `step` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![10.01, 0.3, -0.01];
+    /// let result: Vec<_> = v.titer().vceil_to(0.05).unwrap().collect();
+    /// assert_eq!(result, vec![10.05, 0.3, 0.]);
+    /// ```
+    #[inline]
+    fn vceil_to(self, step: f64) -> TResult<impl TrustedLen<Item = T>>
+    where
+        T::Inner: Number,
+        f64: Cast<T::Inner>,
+    {
+        step_map(self, step, StepRound::Ceil)
+    }
+
+    /// Computes the natural logarithm of each element, None values are NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(1), None];
+    /// let result: Vec<_> = v.titer().vlog().collect();
+    /// assert_eq!(result[0], 0.);
+    /// assert!(result[1].is_nan());
+    /// ```
+    #[inline]
+    fn vlog(self) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, f64::ln)
+    }
+
+    /// Computes `ln(1 + x)` of each element accurately for small values, None values
+    /// are NaN.
+    #[inline]
+    fn vlog1p(self) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, f64::ln_1p)
+    }
+
+    /// Computes the exponential of each element, None values are NaN.
+    #[inline]
+    fn vexp(self) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, f64::exp)
+    }
+
+    /// Computes the square root of each element, None values are NaN.
+    #[inline]
+    fn vsqrt(self) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, f64::sqrt)
+    }
+
+    /// Raises each element to the power `exp`, None values are NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![2, 4];
+    /// let result: Vec<_> = v.titer().vpow(0.5).collect();
+    /// assert_eq!(result, vec![2f64.sqrt(), 2.]);
+    /// ```
+    #[inline]
+    fn vpow(self, exp: f64) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, move |v| v.powf(exp))
+    }
+
+    /// Computes the logistic sigmoid `1 / (1 + exp(-x))` of each element, None values
+    /// are NaN.
+    #[inline]
+    fn vsigmoid(self) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, |v| {
+            // avoid the overflow of exp for large negative values
+            if v >= 0. {
+                1. / (1. + (-v).exp())
+            } else {
+                let e = v.exp();
+                e / (1. + e)
+            }
+        })
+    }
+
+    /// Computes the hyperbolic tangent of each element, None values are NaN.
+    #[inline]
+    fn vtanh(self) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        f64_map(self, f64::tanh)
+    }
+
     /// Forward fill values where the mask is true, ignoring None values.
     ///
     /// # Arguments
@@ -169,6 +394,122 @@ pub trait MapValidBasic<T: IsNone>: TrustedLen<Item = T> + Sized {
         self.bfill_mask(T::is_none, value)
     }
 
+    /// Forward fill None values, but fill at most `limit` consecutive None values after
+    /// each valid value.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of consecutive None values to fill.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(1), None, None, Some(2), None];
+    /// let result: Vec<_> = v.titer().ffill_limit(1).collect();
+    /// assert_eq!(result, vec![Some(1), Some(1), None, Some(2), Some(2)]);
+    /// ```
+    fn ffill_limit(self, limit: usize) -> impl TrustedLen<Item = T> {
+        let mut last_valid: Option<T> = None;
+        let mut count = 0;
+        self.map(move |v: T| {
+            if v.is_none() {
+                count += 1;
+                match last_valid.as_ref() {
+                    Some(lv) if count <= limit => lv.clone(),
+                    _ => v,
+                }
+            } else {
+                count = 0;
+                last_valid = Some(v.clone());
+                v
+            }
+        })
+    }
+
+    /// Backward fill None values, but fill at most `limit` consecutive None values
+    /// before each valid value.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of consecutive None values to fill.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![None, None, Some(1), None, Some(2)];
+    /// let result: Vec<_> = v.tditer().bfill_limit(1).collect();
+    /// assert_eq!(result, vec![None, Some(1), Some(1), Some(2), Some(2)]);
+    /// ```
+    fn bfill_limit(self, limit: usize) -> impl TrustedLen<Item = T>
+    where
+        Self: DoubleEndedIterator<Item = T>,
+    {
+        // see `bfill_mask` for why the result is collected
+        self.rev()
+            .ffill_limit(limit)
+            .collect_trusted_to_vec()
+            .into_iter()
+            .rev()
+    }
+
+    /// Forward fill None values, but only with a valid value observed no more than
+    /// `max_age` before the time of the value to fill.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The time of each value, expected to be sorted.
+    /// * `max_age` - The maximum time elapsed since the valid value used to fill.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `index` doesn't match the length of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let index: Vec<DateTime> = vec![0, 1, 3, 6].into_iter().map(|s| DateTime::new(s * 1_000_000_000)).collect();
+    /// let v = vec![Some(1), None, None, None];
+    /// let max_age = TimeDelta::parse("3s").unwrap();
+    /// let result: Vec<_> = v.titer().ffill_within(index.titer(), max_age).unwrap().collect();
+    /// assert_eq!(result, vec![Some(1), Some(1), Some(1), None]);
+    /// ```
+    #[cfg(feature = "time")]
+    fn ffill_within<I, U>(self, index: I, max_age: TimeDelta) -> TResult<impl TrustedLen<Item = T>>
+    where
+        I: TrustedLen<Item = DateTime<U>>,
+        U: TimeUnitTrait,
+        DateTime<U>: std::ops::Sub<Output = TimeDelta>,
+    {
+        tensure!(
+            TrustedLen::len(&index) == TrustedLen::len(&self),
+            lm,
+            TrustedLen::len(&self),
+            TrustedLen::len(&index)
+        );
+        let mut last_valid: Option<(T, DateTime<U>)> = None;
+        Ok(self.zip(index).map(move |(v, dt)| {
+            if v.is_none() {
+                match last_valid.as_ref() {
+                    // a NaT time delta is not comparable so that the value is not filled
+                    Some((lv, last_dt)) if (dt - *last_dt) <= max_age => lv.clone(),
+                    _ => v,
+                }
+            } else {
+                last_valid = Some((v.clone(), dt));
+                v
+            }
+        }))
+    }
+
     /// Clip (limit) the values in an iterator, ignoring None values.
     ///
     /// This method is similar to `clip()`, but it can handle None values.
@@ -284,6 +625,176 @@ pub trait MapValidBasic<T: IsNone>: TrustedLen<Item = T> + Sized {
         self.fill_mask(T::is_none, value)
     }
 
+    /// Cumulative sum of the values, Kahan summation is used to reduce the numerical
+    /// error of floating point values.
+    ///
+    /// # Arguments
+    ///
+    /// * `skipna` - If true, None values are skipped and kept as None in the output,
+    ///   otherwise all of the values from the first None value on are None.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(1), None, Some(2), Some(3)];
+    /// let result: Vec<_> = v.titer().vcumsum(true).collect();
+    /// assert_eq!(result, vec![Some(1), None, Some(3), Some(6)]);
+    /// let result: Vec<_> = v.titer().vcumsum(false).collect();
+    /// assert_eq!(result, vec![Some(1), None, None, None]);
+    /// ```
+    #[inline]
+    fn vcumsum(self, skipna: bool) -> impl TrustedLen<Item = T>
+    where
+        T::Inner: Number,
+    {
+        let mut sum = T::Inner::zero();
+        let mut c = T::Inner::zero();
+        cum_map(self, skipna, move |v| {
+            sum = sum.kh_sum(v, &mut c);
+            sum
+        })
+    }
+
+    /// Cumulative product of the values.
+    ///
+    /// See [`vcumsum`](MapValidBasic::vcumsum) for the meaning of `skipna`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![1., 2., f64::NAN, 3.];
+    /// let result: Vec<_> = v.titer().vcumprod(true).collect();
+    /// assert_eq!(result[3], 6.);
+    /// assert!(result[2].is_nan());
+    /// ```
+    #[inline]
+    fn vcumprod(self, skipna: bool) -> impl TrustedLen<Item = T>
+    where
+        T::Inner: Number,
+    {
+        let mut prod = T::Inner::one();
+        cum_map(self, skipna, move |v| {
+            prod *= v;
+            prod
+        })
+    }
+
+    /// Cumulative maximum of the values.
+    ///
+    /// See [`vcumsum`](MapValidBasic::vcumsum) for the meaning of `skipna`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(2), Some(1), None, Some(3)];
+    /// let result: Vec<_> = v.titer().vcummax(true).collect();
+    /// assert_eq!(result, vec![Some(2), Some(2), None, Some(3)]);
+    /// ```
+    #[inline]
+    fn vcummax(self, skipna: bool) -> impl TrustedLen<Item = T>
+    where
+        T::Inner: Number,
+    {
+        let mut max = T::Inner::min_();
+        cum_map(self, skipna, move |v| {
+            max = max.max_with(v);
+            max
+        })
+    }
+
+    /// Cumulative minimum of the values.
+    ///
+    /// See [`vcumsum`](MapValidBasic::vcumsum) for the meaning of `skipna`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(2), Some(3), None, Some(1)];
+    /// let result: Vec<_> = v.titer().vcummin(true).collect();
+    /// assert_eq!(result, vec![Some(2), Some(2), None, Some(1)]);
+    /// ```
+    #[inline]
+    fn vcummin(self, skipna: bool) -> impl TrustedLen<Item = T>
+    where
+        T::Inner: Number,
+    {
+        let mut min = T::Inner::max_();
+        cum_map(self, skipna, move |v| {
+            min = min.min_with(v);
+            min
+        })
+    }
+
+    /// Cumulative count of the valid values, the count of a None value is the number
+    /// of valid values before it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(2), None, Some(3), Some(1)];
+    /// let result: Vec<_> = v.titer().vcumcount().collect();
+    /// assert_eq!(result, vec![1, 1, 2, 3]);
+    /// ```
+    #[inline]
+    fn vcumcount(self) -> impl TrustedLen<Item = usize> {
+        let mut n = 0;
+        self.map(move |v| {
+            if v.not_none() {
+                n += 1;
+            }
+            n
+        })
+    }
+
+    /// Cumulative mean of the values, None values are NaN in the output.
+    ///
+    /// See [`vcumsum`](MapValidBasic::vcumsum) for the meaning of `skipna`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(1), Some(3), None, Some(5)];
+    /// let result: Vec<_> = v.titer().vcummean(true).collect();
+    /// assert_eq!(result[..2], [1., 2.]);
+    /// assert_eq!(result[3], 3.);
+    /// ```
+    fn vcummean(self, skipna: bool) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        let (mut sum, mut c, mut n) = (0., 0., 0usize);
+        let mut valid = true;
+        self.map(move |v| match v.to_opt() {
+            Some(v) if valid => {
+                n += 1;
+                sum = sum.kh_sum(v.f64(), &mut c);
+                sum / n as f64
+            },
+            _ => {
+                valid &= skipna;
+                f64::NAN
+            },
+        })
+    }
+
     /// Shift the elements in the iterator, ignoring None values.
     ///
     /// This method is similar to [`shift()`](crate::MapBasic::shift), but it can handle None values.
@@ -587,10 +1098,460 @@ pub trait MapValidBasic<T: IsNone>: TrustedLen<Item = T> + Sized {
             }
         })
     }
+
+    /// Returns the unique values in the order of their first occurrence, all of the
+    /// None values are treated as the same value.
+    ///
+    /// Unlike [`vsorted_unique`](MapValidBasic::vsorted_unique), the input doesn't need
+    /// to be sorted and None is kept in the output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![3., f64::NAN, 1., 3., f64::NAN, 2.];
+    /// let result: Vec<_> = v.titer().vunique().collect();
+    /// assert_eq!(result[0], 3.);
+    /// assert!(result[1].is_nan());
+    /// assert_eq!(result[2..], [1., 2.]);
+    /// ```
+    fn vunique<'a>(self) -> impl Iterator<Item = T> + 'a
+    where
+        T::Inner: HashKey,
+        <T::Inner as HashKey>::Key: 'a,
+        Self: 'a,
+    {
+        let mut seen = HashSet::new();
+        self.filter(move |v| seen.insert(v.as_opt().map(HashKey::hash_key)))
+    }
+
+    /// Counts the occurrences of each unique value.
+    ///
+    /// # Arguments
+    ///
+    /// * `sort` - If true, the values are sorted by the counts in descending order,
+    ///   otherwise they are in the order of their first occurrence.
+    /// * `normalize` - If true, returns the relative frequencies instead of the counts.
+    /// * `dropna` - If true, None values are not counted.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the unique values and their counts, the counts are f64 so that they
+    /// share the type with the relative frequencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some("a"), Some("b"), None, Some("b")];
+    /// let (values, counts) = v.titer().vvalue_counts(true, false, true);
+    /// assert_eq!(values, vec![Some("b"), Some("a")]);
+    /// assert_eq!(counts, vec![2., 1.]);
+    /// ```
+    fn vvalue_counts(self, sort: bool, normalize: bool, dropna: bool) -> (Vec<T>, Vec<f64>)
+    where
+        T::Inner: HashKey,
+    {
+        let mut index = HashMap::new();
+        let mut values = Vec::new();
+        let mut counts: Vec<usize> = Vec::new();
+        for v in self {
+            if dropna && v.is_none() {
+                continue;
+            }
+            let key = v.as_opt().map(HashKey::hash_key);
+            if let Some(&i) = index.get(&key) {
+                counts[i] += 1;
+            } else {
+                index.insert(key, values.len());
+                values.push(v);
+                counts.push(1);
+            }
+        }
+        let total = if normalize {
+            Iterator::sum::<usize>(counts.iter()) as f64
+        } else {
+            1.
+        };
+        let counts = counts.into_iter().map(|c| c as f64 / total);
+        if sort {
+            let mut pairs: Vec<(T, f64)> = values.into_iter().zip(counts).collect();
+            // stable sort keeps the order of the first occurrence for equal counts
+            pairs.sort_by(|a, b| b.1.total_cmp(&a.1));
+            pairs.into_iter().unzip()
+        } else {
+            (values, counts.collect())
+        }
+    }
+
+    /// Encodes the values as integer codes of the unique values.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the codes and the unique values in the order of their first
+    /// occurrence, the code of a None value is None and None is not a unique value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some("b"), Some("a"), None, Some("b")];
+    /// let (codes, uniques): (Vec<Option<i32>>, _) = v.titer().vfactorize();
+    /// assert_eq!(codes, vec![Some(0), Some(1), None, Some(0)]);
+    /// assert_eq!(uniques, vec![Some("b"), Some("a")]);
+    /// ```
+    fn vfactorize<O: Vec1<OT>, OT: IsNone>(self) -> (O, Vec<T>)
+    where
+        T::Inner: HashKey,
+        i32: Cast<OT>,
+    {
+        let mut index = HashMap::new();
+        let mut uniques = Vec::new();
+        let codes = self
+            .map(|v| match v.as_opt().map(HashKey::hash_key) {
+                Some(key) => {
+                    let code = *index.entry(key).or_insert_with(|| {
+                        uniques.push(v.clone());
+                        uniques.len() - 1
+                    });
+                    (code as i32).cast()
+                },
+                None => OT::none(),
+            })
+            .collect_trusted_vec1();
+        (codes, uniques)
+    }
+
+    /// Checks whether each value is in `values`, a None value is in `values` if
+    /// `values` contains any None value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![1, 2, 3, 4];
+    /// let result: Vec<_> = v.titer().visin(&vec![2, 4]).collect();
+    /// assert_eq!(result, vec![false, true, false, true]);
+    /// ```
+    fn visin<V2: Vec1View<T>>(self, values: &V2) -> impl TrustedLen<Item = bool>
+    where
+        T::Inner: HashKey,
+    {
+        let set: HashSet<_> = values
+            .titer()
+            .map(|v| v.as_opt().map(HashKey::hash_key))
+            .collect();
+        self.map(move |v| set.contains(&v.as_opt().map(HashKey::hash_key)))
+    }
+
+    /// Keeps the values where `cond` is true and takes the values of `other` elsewhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lengths of `cond` and `other` don't match the length of
+    /// the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let cond = vec![true, false, true];
+    /// let other = vec![-1, -2, -3];
+    /// let result: Vec<_> = v.titer().vwhere(cond.titer(), other.titer()).unwrap().collect();
+    /// assert_eq!(result, vec![1, -2, 3]);
+    /// ```
+    fn vwhere<C, B, I2>(self, cond: C, other: I2) -> TResult<impl TrustedLen<Item = T>>
+    where
+        C: TrustedLen<Item = B>,
+        B: BoolType,
+        I2: TrustedLen<Item = T>,
+    {
+        let len = TrustedLen::len(&self);
+        tensure!(
+            TrustedLen::len(&cond) == len,
+            lm,
+            len,
+            TrustedLen::len(&cond)
+        );
+        tensure!(
+            TrustedLen::len(&other) == len,
+            lm,
+            len,
+            TrustedLen::len(&other)
+        );
+        Ok(self
+            .zip(cond)
+            .zip(other)
+            .map(|((v, c), o)| if c.bool_() { v } else { o }))
+    }
+
+    /// Replaces the values where `cond` is true with None.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `cond` doesn't match the length of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(1), Some(2), Some(3)];
+    /// let cond = vec![false, true, false];
+    /// let result: Vec<_> = v.titer().vmask(cond.titer()).unwrap().collect();
+    /// assert_eq!(result, vec![Some(1), None, Some(3)]);
+    /// ```
+    fn vmask<C, B>(self, cond: C) -> TResult<impl TrustedLen<Item = T>>
+    where
+        C: TrustedLen<Item = B>,
+        B: BoolType,
+    {
+        let len = TrustedLen::len(&self);
+        tensure!(
+            TrustedLen::len(&cond) == len,
+            lm,
+            len,
+            TrustedLen::len(&cond)
+        );
+        Ok(self
+            .zip(cond)
+            .map(|(v, c)| if c.bool_() { T::none() } else { v }))
+    }
+
+    /// Replaces the values with the mapped values, the values which are not in `map` are
+    /// kept. A None key replaces all of the None values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let v = vec![Some(1), None, Some(3)];
+    /// let result: Vec<_> = v.titer().vreplace([(Some(1), Some(10)), (None, Some(0))]).collect();
+    /// assert_eq!(result, vec![Some(10), Some(0), Some(3)]);
+    /// ```
+    fn vreplace<M>(self, map: M) -> impl TrustedLen<Item = T>
+    where
+        M: IntoIterator<Item = (T, T)>,
+        T::Inner: HashKey,
+    {
+        let map: HashMap<_, T> = map
+            .into_iter()
+            .map(|(k, v)| (k.as_opt().map(HashKey::hash_key), v))
+            .collect();
+        self.map(move |v| match map.get(&v.as_opt().map(HashKey::hash_key)) {
+            Some(new) => new.clone(),
+            None => v,
+        })
+    }
+
+    /// Takes the values of `other` where the values are None, chain the calls to
+    /// coalesce more than two iterators.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `other` doesn't match the length of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidBasic;
+    ///
+    /// let a = vec![Some(1), None, None];
+    /// let b = vec![None, Some(2), None];
+    /// let c = vec![Some(7), Some(8), Some(9)];
+    /// let result: Vec<_> = a
+    ///     .titer()
+    ///     .vcoalesce(b.titer())
+    ///     .unwrap()
+    ///     .vcoalesce(c.titer())
+    ///     .unwrap()
+    ///     .collect();
+    /// assert_eq!(result, vec![Some(1), Some(2), Some(9)]);
+    /// ```
+    fn vcoalesce<I2>(self, other: I2) -> TResult<impl TrustedLen<Item = T>>
+    where
+        I2: TrustedLen<Item = T>,
+    {
+        let len = TrustedLen::len(&self);
+        tensure!(
+            TrustedLen::len(&other) == len,
+            lm,
+            len,
+            TrustedLen::len(&other)
+        );
+        Ok(self
+            .zip(other)
+            .map(|(v, o)| if v.not_none() { v } else { o }))
+    }
 }
 
 impl<T: IsNone, I: TrustedLen<Item = T>> MapValidBasic<T> for I {}
 
+/// Applies `f` to the valid values of `iter` as f64 and casts the results back to the
+/// inner type, None values are kept.
+#[inline]
+fn inner_map<T: IsNone, I: TrustedLen<Item = T>, F: Fn(f64) -> f64>(
+    iter: I,
+    f: F,
+) -> impl TrustedLen<Item = T>
+where
+    T::Inner: Number,
+    f64: Cast<T::Inner>,
+{
+    iter.map(move |v| match v.to_opt() {
+        Some(v) => T::from_inner(f(v.f64()).cast()),
+        None => T::none(),
+    })
+}
+
+/// Applies `f` to the valid values of `iter` as f64, None values are NaN.
+#[inline]
+fn f64_map<T: IsNone, I: TrustedLen<Item = T>, F: Fn(f64) -> f64>(
+    iter: I,
+    f: F,
+) -> impl TrustedLen<Item = f64>
+where
+    T::Inner: Number,
+{
+    iter.map(move |v| v.to_opt().map_or(f64::NAN, |v| f(v.f64())))
+}
+
+/// The direction a value is rounded to a multiple of a step.
+#[derive(Clone, Copy)]
+enum StepRound {
+    Floor,
+    Ceil,
+    /// Halfway cases are rounded away from zero.
+    Nearest,
+}
+
+/// Rounds `v` to the given number of decimals, `scale` is `10^|decimals|`.
+#[inline]
+fn round_decimals(v: f64, decimals: i32, scale: f64) -> f64 {
+    if decimals >= 0 {
+        let scaled = v * scale;
+        // there are no digits to round off if the scaled value is already integral
+        if scaled.abs() >= 2f64.powi(f64::MANTISSA_DIGITS as i32 - 1) || !scaled.is_finite() {
+            v
+        } else {
+            scaled.round() / scale
+        }
+    } else if scale.is_infinite() {
+        // every finite value is less than half of the scale
+        if v.is_finite() { 0. } else { v }
+    } else {
+        (v / scale).round() * scale
+    }
+}
+
+/// Rounds `v` to a multiple of `step` (e.g. the tick size), a value within a relative
+/// error of 1e-9 of a multiple is treated as the multiple itself.
+#[inline]
+fn round_to_step(v: f64, step: f64, mode: StepRound) -> f64 {
+    let q = v / step;
+    let nearest = q.round();
+    let n = if (q - nearest).abs() <= 1e-9 * nearest.abs().max(1.) {
+        nearest
+    } else {
+        match mode {
+            StepRound::Floor => q.floor(),
+            StepRound::Ceil => q.ceil(),
+            StepRound::Nearest => nearest,
+        }
+    };
+    // dividing by an integral inverse (e.g. 100 for 0.01) is exact for decimal steps
+    let inv = 1. / step;
+    if inv.fract() == 0. { n / inv } else { n * step }
+}
+
+/// Rounds the integer `v` to a multiple of the positive integer `step` with integer
+/// arithmetic, the results which overflow saturate at the bounds of the type.
+#[inline]
+fn round_int_to_step<T: Number>(v: T, step: T, mode: StepRound) -> T {
+    let zero = T::default();
+    // the remainder has the same sign as `v`, so `toward` is rounded towards zero
+    let rem = v % step;
+    let toward = v - rem;
+    let away = match mode {
+        StepRound::Floor => rem < zero,
+        StepRound::Ceil => rem > zero,
+        StepRound::Nearest => rem.abs() >= step - rem.abs(),
+    };
+    if !away {
+        toward
+    } else if v < zero {
+        if toward < T::min_() + step {
+            T::min_()
+        } else {
+            toward - step
+        }
+    } else if toward > T::max_() - step {
+        T::max_()
+    } else {
+        toward + step
+    }
+}
+
+/// Rounds the valid values of `iter` to multiples of `step`.
+#[inline]
+fn step_map<T: IsNone, I: TrustedLen<Item = T>>(
+    iter: I,
+    step: f64,
+    mode: StepRound,
+) -> TResult<impl TrustedLen<Item = T>>
+where
+    T::Inner: Number,
+    f64: Cast<T::Inner>,
+{
+    tensure!(
+        step > 0. && step.is_finite(),
+        "step must be positive and finite, find {}",
+        step
+    );
+    let int_step = (!T::Inner::is_float() && step.fract() == 0. && step < T::Inner::max_().f64())
+        .then(|| step.cast());
+    Ok(iter.map(move |v| match v.to_opt() {
+        Some(v) => T::from_inner(match int_step {
+            Some(int_step) => round_int_to_step(v, int_step, mode),
+            None => round_to_step(v.f64(), step, mode).cast(),
+        }),
+        None => T::none(),
+    }))
+}
+
+/// Applies the cumulative function `f` to the valid values of `iter`, None values are
+/// skipped if `skipna` is true, otherwise all of the values after a None value are None.
+#[inline]
+fn cum_map<T: IsNone, I: TrustedLen<Item = T>, F: FnMut(T::Inner) -> T::Inner>(
+    iter: I,
+    skipna: bool,
+    mut f: F,
+) -> impl TrustedLen<Item = T> {
+    let mut valid = true;
+    iter.map(move |v| match v.to_opt() {
+        Some(v) if valid => T::from_inner(f(v)),
+        _ => {
+            valid &= skipna;
+            T::none()
+        },
+    })
+}
+
 #[cfg(test)]
 mod test {
     use tea_core::testing::assert_vec1d_equal_numeric;
@@ -626,6 +1587,214 @@ mod test {
         assert_vec1d_equal_numeric(&res, &vec![0., 1., 2., 0., 3., 0.], None);
     }
 
+    #[test]
+    fn test_fill_limit() {
+        let nan = f64::NAN;
+        let v = vec![nan, 1., nan, nan, nan, 2., nan];
+        let res: Vec<_> = v.titer().ffill_limit(2).collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, 1., 1., 1., nan, 2., 2.], None);
+        let res: Vec<_> = v.titer().ffill_limit(0).collect();
+        assert_vec1d_equal_numeric(&res, &v, None);
+        let res: Vec<_> = v.tditer().bfill_limit(2).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., 1., nan, 2., 2., 2., nan], None);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_ffill_within() -> TResult<()> {
+        let nan = f64::NAN;
+        let index: Vec<DateTime> = [0, 60, 120, 400, 450, 500]
+            .into_iter()
+            .map(|s| DateTime::new(s * 1_000_000_000))
+            .chain(std::iter::once(DateTime::nat()))
+            .collect();
+        let v = vec![1., nan, nan, nan, 2., nan, nan];
+        let max_age = TimeDelta::parse("5m")?;
+        let res: Vec<_> = v.titer().ffill_within(index.titer(), max_age)?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., 1., 1., nan, 2., 2., nan], None);
+        assert!(
+            v.titer()
+                .ffill_within(index.titer().take(3), max_age)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cum() {
+        let nan = f64::NAN;
+        let v = vec![1., nan, 3., -2., 5.];
+        let res: Vec<_> = v.titer().vcumsum(true).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, 4., 2., 7.], None);
+        let res: Vec<_> = v.titer().vcumsum(false).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, nan, nan, nan], None);
+        let res: Vec<_> = v.titer().vcumprod(true).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, 3., -6., -30.], None);
+        let res: Vec<_> = v.titer().vcummax(true).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, 3., 3., 5.], None);
+        let res: Vec<_> = v.titer().vcummin(true).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, 1., -2., -2.], None);
+        let res: Vec<_> = v.titer().vcummean(true).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, 2., 2. / 3., 7. / 4.], None);
+        let res: Vec<_> = v.titer().vcummean(false).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, nan, nan, nan], None);
+        let res: Vec<_> = v.titer().vcumcount().collect();
+        assert_eq!(res, vec![1, 1, 2, 3, 4]);
+        let v = vec![3, 1, 2];
+        let res: Vec<_> = v.titer().vcummin(false).collect();
+        assert_eq!(res, vec![3, 1, 1]);
+        // kahan summation keeps the small values
+        let v = vec![1e16, 1., 1., 1., 1.];
+        let res: Vec<_> = v.titer().vcumsum(true).collect();
+        assert_eq!(res[4], 1e16 + 4.);
+    }
+
+    #[test]
+    fn test_hash_unique() {
+        let nan = f64::NAN;
+        let v = vec![2., nan, 1., -0., 2., 0., nan, 1., 1.];
+        let res: Vec<_> = v.titer().vunique().collect();
+        assert_vec1d_equal_numeric(&res, &vec![2., nan, 1., 0.], None);
+        let (values, counts) = v.titer().vvalue_counts(true, false, false);
+        assert_vec1d_equal_numeric(&values, &vec![1., 2., nan, 0.], None);
+        assert_eq!(counts, vec![3., 2., 2., 2.]);
+        let (values, counts) = v.titer().vvalue_counts(false, true, true);
+        assert_eq!(values, vec![2., 1., -0.]);
+        assert_eq!(counts, vec![2. / 7., 3. / 7., 2. / 7.]);
+        let (codes, uniques): (Vec<f64>, _) = v.titer().vfactorize();
+        assert_vec1d_equal_numeric(&codes, &vec![0., nan, 1., 2., 0., 2., nan, 1., 1.], None);
+        assert_eq!(uniques, vec![2., 1., -0.]);
+        let res: Vec<_> = v.titer().visin(&vec![1., nan]).collect();
+        assert_eq!(
+            res,
+            vec![false, true, true, false, false, false, true, true, true]
+        );
+        let res: Vec<_> = v.titer().visin(&vec![0.]).collect();
+        assert_eq!(
+            res,
+            vec![false, false, false, true, false, true, false, false, false]
+        );
+        let v = vec![Some("a"), None, Some("b"), Some("a")];
+        let res: Vec<_> = v.titer().vunique().collect();
+        assert_eq!(res, vec![Some("a"), None, Some("b")]);
+        let (codes, uniques): (Vec<Option<i32>>, _) = v.titer().vfactorize();
+        assert_eq!(codes, vec![Some(0), None, Some(1), Some(0)]);
+        assert_eq!(uniques, vec![Some("a"), Some("b")]);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_hash_unique_time() {
+        let v: Vec<DateTime> = [3, 1, 3, 2]
+            .into_iter()
+            .map(DateTime::new)
+            .chain(std::iter::once(DateTime::nat()))
+            .collect();
+        let (values, counts) = v.titer().vvalue_counts(true, false, true);
+        assert_eq!(
+            values,
+            vec![DateTime::new(3), DateTime::new(1), DateTime::new(2)]
+        );
+        assert_eq!(counts, vec![2., 1., 1.]);
+        let res: Vec<_> = v.titer().visin(&vec![DateTime::new(1)]).collect();
+        assert_eq!(res, vec![false, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_where() -> TResult<()> {
+        let nan = f64::NAN;
+        let v = vec![1., nan, 3., 4.];
+        let cond = vec![true, true, false, false];
+        let other = vec![-1., -2., -3., nan];
+        let res: Vec<_> = v.titer().vwhere(cond.titer(), other.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, -3., nan], None);
+        let res: Vec<_> = v.titer().vmask(cond.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, 3., 4.], None);
+        let res: Vec<_> = v.titer().vcoalesce(other.titer())?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., -2., 3., 4.], None);
+        let res: Vec<_> = v.titer().vreplace([(3., 30.), (nan, 0.)]).collect();
+        assert_vec1d_equal_numeric(&res, &vec![1., 0., 30., 4.], None);
+        // length mismatch
+        let short = vec![true];
+        assert!(v.titer().vwhere(short.titer(), other.titer()).is_err());
+        assert!(
+            v.titer()
+                .vwhere(cond.titer(), other.titer().take(2))
+                .is_err()
+        );
+        assert!(v.titer().vmask(short.titer()).is_err());
+        assert!(v.titer().vcoalesce(other.titer().take(3)).is_err());
+        let v = vec!["a", "b", "None"];
+        let res: Vec<_> = v.titer().vreplace([("a", "x"), ("None", "z")]).collect();
+        assert_eq!(res, vec!["x", "b", "z"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_math() -> TResult<()> {
+        let nan = f64::NAN;
+        let v = vec![-2.5, nan, 0., 1.];
+        let res: Vec<_> = v.titer().vsign().collect();
+        assert_vec1d_equal_numeric(&res, &vec![-1., nan, 0., 1.], None);
+        let res: Vec<_> = v.titer().vround(0).collect();
+        assert_vec1d_equal_numeric(&res, &vec![-3., nan, 0., 1.], None);
+        let res: Vec<_> = v.titer().vexp().collect();
+        assert_vec1d_equal_numeric(&res, &vec![(-2.5f64).exp(), nan, 1., 1f64.exp()], None);
+        let res: Vec<_> = v.titer().vlog1p().collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, 0., 2f64.ln()], None);
+        let res: Vec<_> = v.titer().vsqrt().collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, 0., 1.], None);
+        let res: Vec<_> = v.titer().vpow(2.).collect();
+        assert_vec1d_equal_numeric(&res, &vec![6.25, nan, 0., 1.], None);
+        let res: Vec<_> = v.titer().vtanh().collect();
+        assert_vec1d_equal_numeric(&res, &vec![(-2.5f64).tanh(), nan, 0., 1f64.tanh()], None);
+        let v = vec![-1000., 0., 1000.];
+        let res: Vec<_> = v.titer().vsigmoid().collect();
+        assert_eq!(res, vec![0., 0.5, 1.]);
+        let v = vec![Some(-7), None, Some(15)];
+        let res: Vec<_> = v.titer().vsign().collect();
+        assert_eq!(res, vec![Some(-1), None, Some(1)]);
+        let res: Vec<_> = v.titer().vfloor_to(5.)?.collect();
+        assert_eq!(res, vec![Some(-10), None, Some(15)]);
+        let res: Vec<_> = v.titer().vceil_to(10.)?.collect();
+        assert_eq!(res, vec![Some(0), None, Some(20)]);
+        let res: Vec<_> = v.titer().vceil_to(2.5)?.collect();
+        assert_eq!(res, vec![Some(-5), None, Some(15)]);
+        assert!(v.titer().vfloor_to(0.).is_err());
+        assert!(v.titer().vceil_to(-1.).is_err());
+        assert!(v.titer().vceil_to(f64::NAN).is_err());
+        // tick sizes which are not exact in binary
+        let v = vec![0.3, 0.7, 1.005, 2.675];
+        let res: Vec<_> = v.titer().vfloor_to(0.1)?.collect();
+        assert_eq!(res, vec![0.3, 0.7, 1., 2.6]);
+        let res: Vec<_> = v.titer().vceil_to(0.01)?.collect();
+        assert_eq!(res, vec![0.3, 0.7, 1.01, 2.68]);
+        // integers beyond 2^53 are exact and the overflowing results saturate
+        let big = (1_i64 << 53) + 1;
+        let v = vec![big, -big, i64::MAX, i64::MIN];
+        let res: Vec<_> = v.titer().vround(2).collect();
+        assert_eq!(res, v);
+        let res: Vec<_> = v.titer().vround(-1).collect();
+        assert_eq!(res, vec![big - 3, 3 - big, i64::MAX, i64::MIN]);
+        let res: Vec<_> = v.titer().vfloor_to(10.)?.collect();
+        assert_eq!(res, vec![big - 3, -big - 7, i64::MAX - 7, i64::MIN]);
+        let res: Vec<_> = v.titer().vceil_to(10.)?.collect();
+        assert_eq!(res, vec![big + 7, 3 - big, i64::MAX, i64::MIN + 8]);
+        let res: Vec<_> = v.titer().vround(-19).collect();
+        assert_eq!(res, vec![0; 4]);
+        let res: Vec<_> = vec![u64::MAX].titer().vround(-19).collect();
+        assert_eq!(res, vec![u64::MAX]);
+        // the scale of large decimals overflows f64
+        let v = vec![1.25, -3.5, 1e300];
+        let res: Vec<_> = v.titer().vround(400).collect();
+        assert_eq!(res, v);
+        let res: Vec<_> = v.titer().vround(-400).collect();
+        assert_eq!(res, vec![0., 0., 0.]);
+        let res: Vec<_> = vec![0.1 + 0.2].titer().vround(20).collect();
+        assert_eq!(res, vec![0.1 + 0.2]);
+        Ok(())
+    }
+
     #[test]
     fn test_vcut() -> TResult<()> {
         let v = vec![1, 3, 5, 1, 5, 6, 7, 32, 1];
@@ -1,6 +1,87 @@
 use std::ops::Sub;
 
+#[cfg(feature = "agg")]
+use tea_agg::{QuantileMethod, VecAggValidExt};
 use tea_core::prelude::*;
+
+use crate::interp::{InterpMethod, LimitDirection, interpolate};
+
+/// Enum representing how to assign ranks to tied elements.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum RankMethod {
+    /// The average of the ranks of the tied group (default).
+    #[default]
+    Average,
+    /// The minimum of the ranks of the tied group.
+    Min,
+    /// The maximum of the ranks of the tied group.
+    Max,
+    /// Like `Min`, but the rank always increases by 1 between groups.
+    Dense,
+    /// Distinct ranks assigned in the order the elements appear (same as `First`,
+    /// the name used by scipy).
+    Ordinal,
+    /// Distinct ranks assigned in the order the elements appear (the name used by pandas).
+    First,
+}
+
+/// Enum representing where None values are placed when ranking or sorting.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum NanPlacement {
+    /// Keep None values as None (default).
+    #[default]
+    Keep,
+    /// Place None values before all valid values.
+    Top,
+    /// Place None values after all valid values.
+    Bottom,
+}
+
+/// Enum representing how ranks are normalized.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum RankNorm {
+    /// Raw ranks starting from 1 (default).
+    #[default]
+    None,
+    /// Ranks divided by the maximum rank, in `(0, 1]`.
+    Pct,
+    /// Ranks linearly mapped from `[1, max_rank]` to `[-1, 1]`, a single ranked
+    /// element gets 0.
+    Signed,
+}
+
+/// Enum representing how the bins of a quantile-based binning are specified.
+#[cfg(feature = "agg")]
+#[derive(Clone, Copy, Debug)]
+pub enum QcutBins<'a> {
+    /// The number of bins with equal frequency.
+    N(usize),
+    /// The quantiles used as bin edges, e.g. `[0., 0.25, 0.5, 0.75, 1.]`.
+    Quantiles(&'a [f64]),
+}
+
+/// Enum representing how duplicated bin edges are handled.
+#[cfg(feature = "agg")]
+#[derive(Default, Clone, Copy, Debug)]
+pub enum Duplicates {
+    /// Return an error if the bin edges are not unique (default).
+    #[default]
+    Raise,
+    /// Drop the duplicated bin edges.
+    Drop,
+}
+
+/// Enum representing which index is returned when searching a value equal to some
+/// elements of a sorted vector.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum SearchSide {
+    /// The index of the first suitable position (default).
+    #[default]
+    Left,
+    /// The index of the last suitable position.
+    Right,
+}
+
 /// Trait for vector-like types that support map operations on valid elements.
 ///
 /// This trait provides methods for performing various operations on vectors,
@@ -99,6 +180,88 @@ pub trait MapValidVec<T: IsNone>: Vec1View<T> {
         }
     }
 
+    /// Calculates the log return between elements in the vector, i.e. the natural
+    /// logarithm of the ratio computed by [`vpct_change`](MapValidVec::vpct_change).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The lag for the log return calculation. Positive values look backward, negative values look forward.
+    ///
+    /// # Returns
+    ///
+    /// A boxed iterator of log returns, all zero if `n` is zero like `vpct_change`.
+    #[inline]
+    fn vlog_return<'a>(&'a self, n: i32) -> Box<dyn TrustedLen<Item = f64> + 'a>
+    where
+        T: Clone + Cast<f64> + 'a,
+        Self: 'a,
+    {
+        if n == 0 {
+            return Box::new(std::iter::repeat_n(0., self.len()));
+        }
+        Box::new(offset_ratio(self, -(n as i64), 0).map(f64::ln))
+    }
+
+    /// Calculates the return from each element to the element `n` positions later,
+    /// a negative `n` gives the return from each element to the element `-n`
+    /// positions earlier.
+    ///
+    /// # Returns
+    ///
+    /// A boxed iterator of forward returns, NaN if the target element is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidVec;
+    ///
+    /// let v = vec![1., 2., 3.];
+    /// let res: Vec<f64> = v.vforward_return(1).collect();
+    /// assert_eq!(res[..2], [1., 0.5]);
+    /// assert!(res[2].is_nan());
+    /// ```
+    #[inline]
+    fn vforward_return<'a>(&'a self, n: i32) -> Box<dyn TrustedLen<Item = f64> + 'a>
+    where
+        T: Clone + Cast<f64> + 'a,
+        Self: 'a,
+    {
+        self.vcompound_return(0, n)
+    }
+
+    /// Calculates the compound return between the offsets `start` and `end` relative to
+    /// each element, i.e. `v[i + end] / v[i + start] - 1`.
+    ///
+    /// `vcompound_return(-n, 0)` is the same as `vpct_change(n)` and
+    /// `vcompound_return(0, n)` is the same as `vforward_return(n)`.
+    ///
+    /// # Returns
+    ///
+    /// A boxed iterator of compound returns, NaN if any of the elements is out of bounds
+    /// or None.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidVec;
+    ///
+    /// let v = vec![1., 2., 4., 5.];
+    /// // return from the next element to the element after it
+    /// let res: Vec<f64> = v.vcompound_return(1, 2).collect();
+    /// assert_eq!(res[..2], [1., 0.25]);
+    /// assert!(res[2].is_nan() && res[3].is_nan());
+    /// ```
+    #[inline]
+    fn vcompound_return<'a>(&'a self, start: i32, end: i32) -> Box<dyn TrustedLen<Item = f64> + 'a>
+    where
+        T: Clone + Cast<f64> + 'a,
+        Self: 'a,
+    {
+        Box::new(offset_ratio(self, start as i64, end as i64).map(|r| r - 1.))
+    }
+
     /// Calculates the rank of elements in the vector.
     ///
     /// # Arguments
@@ -271,6 +434,583 @@ pub trait MapValidVec<T: IsNone>: Vec1View<T> {
         unsafe { out.assume_init() }
     }
 
+    /// Calculates the rank of elements in the vector with the given tie method,
+    /// None placement and normalization.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - How to assign ranks to tied elements, see [`RankMethod`].
+    /// * `nan_placement` - Whether None values are kept as None or ranked before / after
+    ///   all valid values, None values are treated as a tied group when they are ranked.
+    /// * `norm` - How to normalize the ranks, see [`RankNorm`]. The maximum rank is the
+    ///   number of ranked elements, or the number of distinct groups for
+    ///   [`RankMethod::Dense`].
+    /// * `rev` - If true, ranks in descending order.
+    ///
+    /// # Returns
+    ///
+    /// A vector of ranks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidVec, NanPlacement, RankMethod, RankNorm};
+    ///
+    /// let v = vec![2., 1., f64::NAN, 3., 1.];
+    /// let res: Vec<f64> = v.vrank_with(RankMethod::Min, NanPlacement::Bottom, RankNorm::None, false);
+    /// assert_eq!(res, vec![3., 1., 5., 4., 1.]);
+    /// let res: Vec<f64> = v.vrank_with(RankMethod::Dense, NanPlacement::Keep, RankNorm::Signed, false);
+    /// assert_eq!(res[..2], [0., -1.]);
+    /// ```
+    fn vrank_with<O: Vec1<OT>, OT: IsNone>(
+        &self,
+        method: RankMethod,
+        nan_placement: NanPlacement,
+        norm: RankNorm,
+        rev: bool,
+    ) -> O
+    where
+        T: IsNone + PartialEq,
+        T::Inner: PartialOrd,
+        f64: Cast<OT>,
+    {
+        let len = self.len();
+        let (mut valid_idx, nan_idx): (Vec<usize>, Vec<usize>) =
+            (0..len).partition(|i| unsafe { self.uget(*i) }.not_none());
+        // stable sort so that tied elements keep the order they appear
+        if !rev {
+            valid_idx.sort_by(|a, b| {
+                let (va, vb) = unsafe { (self.uget(*a), self.uget(*b)) };
+                va.sort_cmp(&vb)
+            });
+        } else {
+            valid_idx.sort_by(|a, b| {
+                let (va, vb) = unsafe { (self.uget(*a), self.uget(*b)) };
+                va.sort_cmp_rev(&vb)
+            });
+        }
+        // split the ranked elements into groups of tied elements
+        let mut groups: Vec<&[usize]> = Vec::new();
+        if matches!(nan_placement, NanPlacement::Top) && !nan_idx.is_empty() {
+            groups.push(&nan_idx);
+        }
+        let mut group_start = 0;
+        for i in 1..=valid_idx.len() {
+            if i == valid_idx.len()
+                || unsafe { self.uget(valid_idx[i]) != self.uget(valid_idx[group_start]) }
+            {
+                groups.push(&valid_idx[group_start..i]);
+                group_start = i;
+            }
+        }
+        if matches!(nan_placement, NanPlacement::Bottom) && !nan_idx.is_empty() {
+            groups.push(&nan_idx);
+        }
+        let n = match nan_placement {
+            NanPlacement::Keep => valid_idx.len(),
+            _ => len,
+        };
+        let max_rank = match method {
+            RankMethod::Dense => groups.len(),
+            _ => n,
+        }
+        .f64();
+        let normalize = |rank: f64| -> f64 {
+            match norm {
+                RankNorm::None => rank,
+                RankNorm::Pct => rank / max_rank,
+                RankNorm::Signed => {
+                    if max_rank > 1. {
+                        2. * (rank - 1.) / (max_rank - 1.) - 1.
+                    } else {
+                        0.
+                    }
+                },
+            }
+        };
+        let mut out = O::uninit(len);
+        if matches!(nan_placement, NanPlacement::Keep) {
+            for i in &nan_idx {
+                unsafe { out.uset(*i, f64::NAN.cast()) }
+            }
+        }
+        // the number of elements ranked before the current group
+        let mut count = 0usize;
+        for (dense_rank, group) in groups.into_iter().enumerate() {
+            let size = group.len();
+            for (j, i) in group.iter().enumerate() {
+                let rank = match method {
+                    RankMethod::Average => count.f64() + (size + 1).f64() / 2.,
+                    RankMethod::Min => (count + 1).f64(),
+                    RankMethod::Max => (count + size).f64(),
+                    RankMethod::Dense => (dense_rank + 1).f64(),
+                    RankMethod::Ordinal | RankMethod::First => (count + j + 1).f64(),
+                };
+                unsafe { out.uset(*i, normalize(rank).cast()) }
+            }
+            count += size;
+        }
+        unsafe { out.assume_init() }
+    }
+
+    /// Computes the bin edges of a quantile-based binning from the valid elements.
+    ///
+    /// The edges are the linearly interpolated quantiles of the valid elements, they
+    /// are all NaN if there is no valid element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of bins is zero, a quantile is not between 0 and 1,
+    /// the edges are not increasing or they are duplicated with [`Duplicates::Raise`].
+    #[cfg(feature = "agg")]
+    fn vqcut_edges(&self, bins: QcutBins, duplicates: Duplicates) -> TResult<Vec<f64>>
+    where
+        T: Cast<f64>,
+        T::Inner: Number,
+    {
+        let quantiles: Vec<f64> = match bins {
+            QcutBins::N(n) => {
+                tensure!(n > 0, "number of bins must be positive");
+                (0..=n).map(|i| i.f64() / n.f64()).collect()
+            },
+            QcutBins::Quantiles(q) => q.to_vec(),
+        };
+        let valid: Vec<f64> = self
+            .titer()
+            .filter(IsNone::not_none)
+            .map(|v| v.cast())
+            .collect();
+        let mut edges = quantiles
+            .into_iter()
+            .map(|q| valid.vquantile(q, QuantileMethod::Linear))
+            .collect::<TResult<Vec<f64>>>()?;
+        if valid.is_empty() {
+            return Ok(edges);
+        }
+        for i in 1..edges.len() {
+            tensure!(
+                edges[i] >= edges[i - 1],
+                "bin edges must be increasing, find {:?}",
+                edges
+            );
+            if edges[i] == edges[i - 1] && matches!(duplicates, Duplicates::Raise) {
+                tbail!(
+                    func = qcut,
+                    "bin edges must be unique: {:?}, you can drop duplicate edges by setting `duplicates` to `Drop`",
+                    edges
+                )
+            }
+        }
+        edges.dedup();
+        Ok(edges)
+    }
+
+    /// Categorizes the elements into quantile-based bins, returning the bin codes.
+    ///
+    /// The bins are closed on the right, except that the first bin also contains the
+    /// minimum value, so the codes of valid elements are `0..n_bins`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bins` - The number of equal-frequency bins or the quantiles used as bin edges.
+    /// * `duplicates` - Whether to raise an error or drop duplicated bin edges.
+    /// * `retbins` - If true, the bin edges are returned as well.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the bin codes (None for None elements) and the bin edges if `retbins`
+    /// is true.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{Duplicates, MapValidVec, QcutBins};
+    ///
+    /// let v = vec![1., 2., 3., 4., f64::NAN, 5.];
+    /// let (codes, edges): (Vec<Option<i32>>, _) = v.vqcut_codes(QcutBins::N(2), Duplicates::Raise, true).unwrap();
+    /// assert_eq!(codes, vec![Some(0), Some(0), Some(0), Some(1), None, Some(1)]);
+    /// assert_eq!(edges, Some(vec![1., 3., 5.]));
+    /// ```
+    #[cfg(feature = "agg")]
+    fn vqcut_codes<O: Vec1<OT>, OT: IsNone>(
+        &self,
+        bins: QcutBins,
+        duplicates: Duplicates,
+        retbins: bool,
+    ) -> TResult<(O, Option<Vec<f64>>)>
+    where
+        T: Cast<f64>,
+        T::Inner: Number,
+        i32: Cast<OT>,
+    {
+        let edges = self.vqcut_edges(bins, duplicates)?;
+        let out = self
+            .titer()
+            .map(|v| {
+                qcut_code(&edges, v)
+                    .map(|code| (code as i32).cast())
+                    .unwrap_or_else(OT::none)
+            })
+            .collect_trusted_vec1();
+        Ok((out, retbins.then_some(edges)))
+    }
+
+    /// Categorizes the elements into quantile-based bins, returning the bin labels.
+    ///
+    /// The bins are closed on the right, except that the first bin also contains the
+    /// minimum value.
+    ///
+    /// # Arguments
+    ///
+    /// * `bins` - The number of equal-frequency bins or the quantiles used as bin edges.
+    /// * `labels` - The labels of the bins, the number of labels must be equal to the
+    ///   number of bins (after dropping duplicated edges).
+    /// * `duplicates` - Whether to raise an error or drop duplicated bin edges.
+    /// * `retbins` - If true, the bin edges are returned as well.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the bin labels (None for None elements) and the bin edges if `retbins`
+    /// is true.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the edges can not be computed (see [`MapValidVec::vqcut_edges`])
+    /// or the number of labels doesn't match the number of bins.
+    #[cfg(feature = "agg")]
+    fn vqcut<O: Vec1<T2>, T2: IsNone + Clone, V3: Vec1View<T2>>(
+        &self,
+        bins: QcutBins,
+        labels: &V3,
+        duplicates: Duplicates,
+        retbins: bool,
+    ) -> TResult<(O, Option<Vec<f64>>)>
+    where
+        T: Cast<f64>,
+        T::Inner: Number,
+    {
+        let edges = self.vqcut_edges(bins, duplicates)?;
+        if !edges.is_empty() && !edges[0].is_nan() && labels.len() + 1 != edges.len() {
+            tbail!(
+                func = qcut,
+                "Number of labels must be equal to the number of bins, label: {}, bins: {}",
+                labels.len(),
+                edges.len() - 1
+            )
+        }
+        let out = self
+            .titer()
+            .map(|v| {
+                qcut_code(&edges, v)
+                    .map(|code| unsafe { labels.uget(code) })
+                    .unwrap_or_else(T2::none)
+            })
+            .collect_trusted_vec1();
+        Ok((out, retbins.then_some(edges)))
+    }
+
+    /// Fills None values by interpolating the valid values, the positions of the
+    /// elements are used as the x-coordinates.
+    ///
+    /// Missing values before the first (after the last) valid value can only be filled
+    /// backward (forward), they are filled with the first (last) valid value except for
+    /// [`InterpMethod::Previous`] ([`InterpMethod::Next`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The interpolation method, see [`InterpMethod`].
+    /// * `limit` - The maximum number of consecutive None values to fill in `limit_direction`.
+    /// * `limit_direction` - Whether None values after and/or before a valid value are filled.
+    ///
+    /// # Returns
+    ///
+    /// An iterator of the interpolated values, None values are NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{InterpMethod, LimitDirection, MapValidVec};
+    ///
+    /// let v = vec![None, Some(1.), None, None, Some(4.), None];
+    /// let res: Vec<f64> = v.vinterpolate(InterpMethod::Linear, None, LimitDirection::Forward).collect();
+    /// assert!(res[0].is_nan());
+    /// assert_eq!(res[1..], [1., 2., 3., 4., 4.]);
+    /// ```
+    fn vinterpolate(
+        &self,
+        method: InterpMethod,
+        limit: Option<usize>,
+        limit_direction: LimitDirection,
+    ) -> impl TrustedLen<Item = f64>
+    where
+        T::Inner: Number,
+    {
+        let x: Vec<f64> = (0..self.len()).map(|i| i.f64()).collect();
+        let y: Vec<f64> = self
+            .titer()
+            .map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64()))
+            .collect();
+        let within = |p: usize, i: usize| limit.is_none_or(|limit| i - p <= limit);
+        interpolate(&x, &y, method, within, limit_direction).into_iter()
+    }
+
+    /// Fills None values by interpolating the valid values, the timestamps of `index`
+    /// are used as the x-coordinates so the interpolation is weighted by time.
+    ///
+    /// The timestamps of `index` other than NaT must be strictly increasing, values at a
+    /// NaT timestamp are not filled. See [`MapValidVec::vinterpolate`] for `method` and
+    /// `limit_direction`.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum time span between a filled value and the valid value it
+    ///   is filled from in `limit_direction`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `index` doesn't match the length of the vector
+    /// or the timestamps are not strictly increasing.
+    #[cfg(feature = "time")]
+    fn vinterpolate_time<V2: Vec1View<DateTime<U>>, U: TimeUnitTrait>(
+        &self,
+        index: &V2,
+        method: InterpMethod,
+        limit: Option<TimeDelta>,
+        limit_direction: LimitDirection,
+    ) -> TResult<impl TrustedLen<Item = f64>>
+    where
+        T::Inner: Number,
+        DateTime<U>: Sub<Output = TimeDelta>,
+    {
+        tensure!(index.len() == self.len(), lm, self.len(), index.len());
+        // the timestamps are relative to the first one so that they are exact in f64
+        let start = index.titer().find_map(|dt| dt.into_opt_i64()).unwrap_or(0);
+        let x: Vec<f64> = index
+            .titer()
+            .map(|dt| dt.into_opt_i64().map_or(f64::NAN, |v| (v - start).f64()))
+            .collect();
+        let mut last = f64::NEG_INFINITY;
+        for &v in x.iter().filter(|v| v.not_none()) {
+            tensure!(
+                v > last,
+                "the timestamps of index must be strictly increasing"
+            );
+            last = v;
+        }
+        let y: Vec<f64> = self
+            .titer()
+            .map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64()))
+            .collect();
+        // safety: the indices are less than the length of the index
+        let within = |p: usize, i: usize| {
+            limit.is_none_or(|limit| unsafe { index.uget(i) - index.uget(p) } <= limit)
+        };
+        Ok(interpolate(&x, &y, method, within, limit_direction).into_iter())
+    }
+
+    /// Shifts the vector by time, the value of each element is the value of the most
+    /// recent observation at or before `index - delta`.
+    ///
+    /// A positive `delta` lags the values (e.g. the value as of one day ago) and a
+    /// negative `delta` leads them. `index` is expected to be sorted in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The time of each element.
+    /// * `delta` - The time to shift by, calendar months are supported.
+    /// * `tolerance` - The maximum time between the shifted time and the observation,
+    ///   the value is None if the most recent observation is older.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of `index` doesn't match the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::MapValidVec;
+    ///
+    /// let index: Vec<DateTime> = vec![0, 60, 90, 300]
+    ///     .into_iter()
+    ///     .map(|s| DateTime::new(s * 1_000_000_000))
+    ///     .collect();
+    /// let v = vec![Some(1), Some(2), Some(3), Some(4)];
+    /// let delta = TimeDelta::parse("1m").unwrap();
+    /// let res: Vec<_> = v.vshift_by_time(&index, delta, None).unwrap().collect();
+    /// assert_eq!(res, vec![None, Some(1), Some(1), Some(3)]);
+    /// let tolerance = Some(TimeDelta::parse("1m").unwrap());
+    /// let res: Vec<_> = v.vshift_by_time(&index, delta, tolerance).unwrap().collect();
+    /// assert_eq!(res, vec![None, Some(1), Some(1), None]);
+    /// ```
+    #[cfg(feature = "time")]
+    fn vshift_by_time<'a, V2, U>(
+        &'a self,
+        index: &'a V2,
+        delta: TimeDelta,
+        tolerance: Option<TimeDelta>,
+    ) -> TResult<Box<dyn TrustedLen<Item = T> + 'a>>
+    where
+        T: 'a,
+        V2: Vec1View<DateTime<U>>,
+        U: TimeUnitTrait + 'a,
+        DateTime<U>: Sub<TimeDelta, Output = DateTime<U>> + Sub<Output = TimeDelta>,
+    {
+        let len = self.len();
+        tensure!(index.len() == len, lm, len, index.len());
+        let targets: Vec<DateTime<U>> = index.titer().map(|t| t - delta).collect();
+        // the number of observations at or before each target
+        let ends: Vec<usize> = index.vsearchsorted(&targets, SearchSide::Right);
+        Ok(Box::new(targets.into_iter().zip(ends).map(
+            move |(target, end)| {
+                if target.is_nat() || end == 0 {
+                    return T::none();
+                }
+                // safety: `end` is at most the length of the index
+                let found = unsafe { index.uget(end - 1) };
+                if tolerance.is_none_or(|tolerance| target - found <= tolerance) {
+                    unsafe { self.uget(end - 1) }
+                } else {
+                    T::none()
+                }
+            },
+        )))
+    }
+
+    /// Returns the indices that would sort the vector, the sort is stable so that equal
+    /// elements keep the order they appear.
+    ///
+    /// # Arguments
+    ///
+    /// * `rev` - If true, sorts in descending order.
+    /// * `nan_placement` - Where None values are placed, [`NanPlacement::Keep`] keeps
+    ///   them at their original positions and sorts the valid elements around them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidVec, NanPlacement};
+    ///
+    /// let v = vec![3., f64::NAN, 1., 3., 2.];
+    /// let idx: Vec<usize> = v.vargsort(false, NanPlacement::Bottom);
+    /// assert_eq!(idx, vec![2, 4, 0, 3, 1]);
+    /// let idx: Vec<usize> = v.vargsort(true, NanPlacement::Keep);
+    /// assert_eq!(idx, vec![0, 1, 3, 4, 2]);
+    /// ```
+    fn vargsort<O: Vec1<usize>>(&self, rev: bool, nan_placement: NanPlacement) -> O
+    where
+        T::Inner: PartialOrd,
+    {
+        let len = self.len();
+        let (mut valid_idx, nan_idx): (Vec<usize>, Vec<usize>) =
+            (0..len).partition(|i| unsafe { self.uget(*i) }.not_none());
+        // safety: the indices are less than the length of the vector
+        if !rev {
+            valid_idx.sort_by(|a, b| unsafe { self.uget(*a).sort_cmp(&self.uget(*b)) });
+        } else {
+            valid_idx.sort_by(|a, b| unsafe { self.uget(*a).sort_cmp_rev(&self.uget(*b)) });
+        }
+        match nan_placement {
+            NanPlacement::Top => nan_idx.into_iter().chain(valid_idx).collect_trusted_vec1(),
+            NanPlacement::Bottom => valid_idx.into_iter().chain(nan_idx).collect_trusted_vec1(),
+            NanPlacement::Keep => {
+                let mut sorted = valid_idx.into_iter();
+                (0..len)
+                    .map(|i| {
+                        if unsafe { self.uget(i) }.is_none() {
+                            i
+                        } else {
+                            sorted.next().unwrap()
+                        }
+                    })
+                    .collect_trusted_vec1()
+            },
+        }
+    }
+
+    /// Sorts the vector, the sort is stable so that equal elements keep the order they
+    /// appear.
+    ///
+    /// See [`vargsort`](MapValidVec::vargsort) for the arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidVec, NanPlacement};
+    ///
+    /// let v = vec![Some(3), None, Some(1), Some(2)];
+    /// let res: Vec<_> = v.vsort(false, NanPlacement::Top);
+    /// assert_eq!(res, vec![None, Some(1), Some(2), Some(3)]);
+    /// let res: Vec<_> = v.vsort(false, NanPlacement::Keep);
+    /// assert_eq!(res, vec![Some(1), None, Some(2), Some(3)]);
+    /// ```
+    fn vsort<O: Vec1<T>>(&self, rev: bool, nan_placement: NanPlacement) -> O
+    where
+        T::Inner: PartialOrd,
+    {
+        let idx: Vec<usize> = self.vargsort(rev, nan_placement);
+        // safety: the indices are less than the length of the vector
+        idx.into_iter()
+            .map(|i| unsafe { self.uget(i) })
+            .collect_trusted_vec1()
+    }
+
+    /// Finds the indices where `values` should be inserted to keep the vector sorted.
+    ///
+    /// The vector is expected to be sorted in ascending order with None values at the
+    /// end, a None value is inserted before (after) the None values of the vector with
+    /// [`SearchSide::Left`] ([`SearchSide::Right`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to insert.
+    /// * `side` - Which index is returned if there are elements equal to a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tea_core::prelude::*;
+    /// use tea_map::{MapValidVec, SearchSide};
+    ///
+    /// let v = vec![1, 2, 2, 3];
+    /// let idx: Vec<usize> = v.vsearchsorted(&vec![0, 2, 5], SearchSide::Left);
+    /// assert_eq!(idx, vec![0, 1, 4]);
+    /// let idx: Vec<usize> = v.vsearchsorted(&vec![0, 2, 5], SearchSide::Right);
+    /// assert_eq!(idx, vec![0, 3, 4]);
+    /// ```
+    fn vsearchsorted<O: Vec1<usize>, V2: Vec1View<T>>(&self, values: &V2, side: SearchSide) -> O
+    where
+        T::Inner: PartialOrd,
+    {
+        values
+            .titer()
+            .map(|v| {
+                // binary search of the first element which should be after v
+                let (mut lo, mut hi) = (0, self.len());
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    // safety: mid is less than the length of the vector
+                    let ord = unsafe { self.uget(mid) }.sort_cmp(&v);
+                    let before = match side {
+                        SearchSide::Left => ord.is_lt(),
+                        SearchSide::Right => ord.is_le(),
+                    };
+                    if before {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                lo
+            })
+            .collect_trusted_vec1()
+    }
+
     /// Returns the indices of the kth smallest elements.
     ///
     /// # Arguments
@@ -407,6 +1147,47 @@ pub trait MapValidVec<T: IsNone>: Vec1View<T> {
 
 impl<T: IsNone, I: Vec1View<T>> MapValidVec<T> for I {}
 
+/// Returns the code of the right-closed bin which contains `v`, the first bin also
+/// contains the first edge.
+#[cfg(feature = "agg")]
+#[inline]
+fn qcut_code<T: IsNone + Cast<f64>>(edges: &[f64], v: T) -> Option<usize> {
+    if v.is_none() || edges.len() < 2 {
+        return None;
+    }
+    let v: f64 = v.cast();
+    if v == edges[0] {
+        Some(0)
+    } else {
+        let idx = edges.partition_point(|e| *e < v);
+        (idx > 0 && idx < edges.len()).then(|| idx - 1)
+    }
+}
+
+/// Returns `v[i + end] / v[i + start]` for each position `i`, NaN if any of the elements
+/// is out of bounds or None, or the denominator is zero.
+fn offset_ratio<V: Vec1View<T> + ?Sized, T: IsNone + Cast<f64>>(
+    vec: &V,
+    start: i64,
+    end: i64,
+) -> impl TrustedLen<Item = f64> + '_ {
+    let len = vec.len() as i64;
+    (0..vec.len()).map(move |i| {
+        let (a, b) = (i as i64 + start, i as i64 + end);
+        if a < 0 || b < 0 || a >= len || b >= len {
+            return f64::NAN;
+        }
+        // safety: the indices are checked above
+        let (a, b) = unsafe { (vec.uget(a as usize), vec.uget(b as usize)) };
+        if a.not_none() && b.not_none() {
+            let a: f64 = a.cast();
+            if a != 0. { b.cast() / a } else { f64::NAN }
+        } else {
+            f64::NAN
+        }
+    })
+}
+
 #[cfg(test)]
 mod test {
     use tea_core::testing::assert_vec1d_equal_numeric;
@@ -437,6 +1218,36 @@ mod test {
         assert_vec1d_equal_numeric(&res, &vec![-0.5, -1. / 3., -1. / 3., f64::NAN], None);
     }
 
+    #[test]
+    fn test_returns() {
+        let nan = f64::NAN;
+        let v = vec![1., 2., nan, 4., 8.];
+        let res: Vec<_> = v.vlog_return(1).collect_trusted_vec1();
+        let ln2 = 2f64.ln();
+        assert_vec1d_equal_numeric(&res, &vec![nan, ln2, nan, nan, ln2], None);
+        let res: Vec<_> = v.vlog_return(-2).collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![nan, -ln2, nan, nan, nan], None);
+        // a zero lag gives zero returns like vpct_change
+        let res: Vec<_> = v.vlog_return(0).collect_trusted_vec1();
+        let expect: Vec<_> = v.vpct_change(0).collect_trusted_vec1();
+        assert_eq!(res, expect);
+        let res: Vec<_> = v.vlog_return(i32::MIN).collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![nan; 5], None);
+        let res: Vec<_> = v.vforward_return(1).collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![1., nan, nan, 1., nan], None);
+        let res: Vec<_> = v.vforward_return(-1).collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![nan, -0.5, nan, nan, -0.5], None);
+        let res: Vec<_> = v.vcompound_return(-1, 1).collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, 1., nan, nan], None);
+        // consistent with vpct_change
+        let v = vec![Some(1), Some(2), None, Some(4), Some(0), Some(3)];
+        for n in [-2, -1, 1, 3, 10] {
+            let res: Vec<_> = v.vcompound_return(-n, 0).collect_trusted_vec1();
+            let expect: Vec<_> = v.vpct_change(n).collect_trusted_vec1();
+            assert_vec1d_equal_numeric(&res, &expect, None);
+        }
+    }
+
     #[test]
     fn test_rank() {
         let v = vec![2., 1., f64::NAN, 3., 1.];
@@ -448,6 +1259,304 @@ mod test {
         assert_vec1d_equal_numeric(&res, &expect, None);
     }
 
+    #[test]
+    fn test_rank_with() {
+        let v = vec![2., 1., f64::NAN, 3., 1., 2.];
+        let rank = |method, nan_placement, norm, rev| -> Vec<f64> {
+            v.vrank_with(method, nan_placement, norm, rev)
+        };
+        use NanPlacement::*;
+        use RankMethod::*;
+        let nan = f64::NAN;
+        assert_vec1d_equal_numeric(
+            &rank(Average, Keep, RankNorm::None, false),
+            &vec![3.5, 1.5, nan, 5., 1.5, 3.5],
+            None,
+        );
+        // the default options are the same as vrank
+        let expect: Vec<f64> = v.vrank(true, true);
+        assert_vec1d_equal_numeric(&rank(Average, Keep, RankNorm::Pct, true), &expect, None);
+        assert_vec1d_equal_numeric(
+            &rank(Min, Keep, RankNorm::None, false),
+            &vec![3., 1., nan, 5., 1., 3.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(Max, Keep, RankNorm::None, false),
+            &vec![4., 2., nan, 5., 2., 4.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(Dense, Keep, RankNorm::None, false),
+            &vec![2., 1., nan, 3., 1., 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(First, Keep, RankNorm::None, false),
+            &vec![3., 1., nan, 5., 2., 4.],
+            None,
+        );
+        // ties keep the order they appear in descending order as well
+        assert_vec1d_equal_numeric(
+            &rank(Ordinal, Keep, RankNorm::None, true),
+            &vec![2., 4., nan, 1., 5., 3.],
+            None,
+        );
+        assert_eq!(
+            rank(Min, Top, RankNorm::None, false),
+            vec![4., 2., 1., 6., 2., 4.]
+        );
+        assert_eq!(
+            rank(Dense, Bottom, RankNorm::Pct, false),
+            vec![0.5, 0.25, 1., 0.75, 0.25, 0.5]
+        );
+        assert_vec1d_equal_numeric(
+            &rank(Average, Keep, RankNorm::Signed, false),
+            &vec![0.25, -0.75, nan, 1., -0.75, 0.25],
+            None,
+        );
+        let res: Vec<f64> = vec![1.].vrank_with(Min, Keep, RankNorm::Signed, false);
+        assert_eq!(res, vec![0.]);
+        let res: Vec<Option<f64>> =
+            vec![None, Some(1.)].vrank_with(Min, Keep, RankNorm::None, false);
+        assert_eq!(res, vec![None, Some(1.)]);
+        let res: Vec<f64> = Vec::<f64>::new().vrank_with(Min, Keep, RankNorm::None, false);
+        assert!(res.is_empty());
+    }
+
+    #[cfg(feature = "agg")]
+    #[test]
+    fn test_qcut() {
+        let v = vec![5., 1., 9., f64::NAN, 3., 7., 2., 8., 4., 6., 10.];
+        let (res, edges): (Vec<Option<i32>>, _) = v
+            .vqcut_codes(QcutBins::N(4), Duplicates::Raise, true)
+            .unwrap();
+        assert_eq!(edges, Some(vec![1., 3.25, 5.5, 7.75, 10.]));
+        let expect = vec![1, 0, 3, -1, 0, 2, 0, 3, 1, 2, 3];
+        let expect: Vec<_> = expect.into_iter().map(|v| (v >= 0).then_some(v)).collect();
+        assert_eq!(res, expect);
+        let (res, edges): (Vec<Option<&str>>, _) = v
+            .vqcut(
+                QcutBins::Quantiles(&[0., 0.5, 1.]),
+                &vec![Some("low"), Some("high")],
+                Duplicates::Raise,
+                false,
+            )
+            .unwrap();
+        assert_eq!(edges, None);
+        assert_eq!(res[..4], [Some("low"), Some("low"), Some("high"), None]);
+        // duplicated edges
+        let v = vec![1., 1., 1., 1., 2., 3.];
+        assert!(
+            v.vqcut_codes::<Vec<Option<i32>>, _>(QcutBins::N(3), Duplicates::Raise, false)
+                .is_err()
+        );
+        let (res, edges): (Vec<Option<i32>>, _) = v
+            .vqcut_codes(QcutBins::N(3), Duplicates::Drop, true)
+            .unwrap();
+        assert_eq!(edges, Some(vec![1., 1.3333333333333333, 3.]));
+        assert_eq!(
+            res,
+            vec![Some(0), Some(0), Some(0), Some(0), Some(1), Some(1)]
+        );
+        // the number of labels doesn't match after dropping duplicated edges
+        assert!(
+            v.vqcut::<Vec<Option<i32>>, _, _>(
+                QcutBins::N(3),
+                &vec![Some(1), Some(2), Some(3)],
+                Duplicates::Drop,
+                false
+            )
+            .is_err()
+        );
+        assert!(
+            v.vqcut_codes::<Vec<Option<i32>>, _>(QcutBins::N(0), Duplicates::Raise, false)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let nan = f64::NAN;
+        let v = vec![nan, 1., nan, nan, 4., nan, 8., nan, nan];
+        let interp = |method, limit, dir| -> Vec<f64> {
+            v.vinterpolate(method, limit, dir).collect_trusted_vec1()
+        };
+        use InterpMethod::*;
+        use LimitDirection::*;
+        assert_vec1d_equal_numeric(
+            &interp(Linear, None, Forward),
+            &vec![nan, 1., 2., 3., 4., 6., 8., 8., 8.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &interp(Linear, Some(1), Forward),
+            &vec![nan, 1., 2., nan, 4., 6., 8., 8., nan],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &interp(Linear, Some(1), Backward),
+            &vec![1., 1., nan, 3., 4., 6., 8., nan, nan],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &interp(Linear, Some(1), Both),
+            &vec![1., 1., 2., 3., 4., 6., 8., 8., nan],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &interp(Nearest, None, Both),
+            &vec![1., 1., 1., 4., 4., 4., 8., 8., 8.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &interp(Previous, None, Both),
+            &vec![nan, 1., 1., 1., 4., 4., 8., 8., 8.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &interp(Next, None, Both),
+            &vec![1., 1., 4., 4., 4., 8., 8., nan, nan],
+            None,
+        );
+        // a quadratic is recovered exactly by polynomial and spline of enough points
+        let v: Vec<Option<f64>> = (0..8)
+            .map(|i| (i % 3 != 2).then(|| (i * i) as f64))
+            .collect();
+        let res: Vec<f64> = v
+            .vinterpolate(Polynomial(2), None, Forward)
+            .collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![0., 1., 4., 9., 16., 25., 36., 49.], Some(1e-10));
+        let res: Vec<f64> = v
+            .vinterpolate(CubicSpline, None, Forward)
+            .collect_trusted_vec1();
+        // the values of the natural cubic spline computed with exact rational arithmetic
+        assert_vec1d_equal_numeric(
+            &vec![res[2], res[5]],
+            &vec![299. / 76., 1895. / 76.],
+            Some(1e-9),
+        );
+        // a straight line is recovered exactly by the natural cubic spline
+        let v = vec![0., nan, 2., 3., nan, nan, 6.];
+        let res: Vec<f64> = v
+            .vinterpolate(CubicSpline, None, Forward)
+            .collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![0., 1., 2., 3., 4., 5., 6.], Some(1e-10));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_interpolate_time() {
+        let nan = f64::NAN;
+        let index: Vec<DateTime> = [0, 1, 3, 4, i64::MIN]
+            .into_iter()
+            .map(DateTime::new)
+            .collect();
+        let v = vec![0., nan, 6., nan, nan];
+        let res: Vec<f64> = v
+            .vinterpolate_time(&index, InterpMethod::Linear, None, LimitDirection::Forward)
+            .unwrap()
+            .collect_trusted_vec1();
+        assert_vec1d_equal_numeric(&res, &vec![0., 2., 6., 6., nan], None);
+        assert!(
+            v.vinterpolate_time(
+                &index[..3].to_vec(),
+                InterpMethod::Linear,
+                None,
+                LimitDirection::Forward
+            )
+            .is_err()
+        );
+        // the limit is a time span rather than a number of elements
+        let index: Vec<DateTime> = [0, 50, 55, 60, 70]
+            .into_iter()
+            .map(|s| DateTime::new(s * 1_000_000_000))
+            .collect();
+        let v = vec![0., nan, nan, nan, 7.];
+        let interp = |limit: &str, dir| -> Vec<f64> {
+            let limit = Some(TimeDelta::parse(limit).unwrap());
+            v.vinterpolate_time(&index, InterpMethod::Linear, limit, dir)
+                .unwrap()
+                .collect_trusted_vec1()
+        };
+        assert_vec1d_equal_numeric(
+            &interp("52s", LimitDirection::Forward),
+            &vec![0., 5., nan, nan, 7.],
+            Some(1e-10),
+        );
+        assert_vec1d_equal_numeric(
+            &interp("15s", LimitDirection::Backward),
+            &vec![0., nan, 5.5, 6., 7.],
+            Some(1e-10),
+        );
+        // repeated or decreasing timestamps are rejected
+        for secs in [[0, 1, 1, 2], [0, 2, 1, 3]] {
+            let index: Vec<DateTime> = secs.into_iter().map(DateTime::new).collect();
+            assert!(
+                vec![0., nan, nan, 3.]
+                    .vinterpolate_time(&index, InterpMethod::Linear, None, LimitDirection::Forward)
+                    .is_err()
+            );
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_shift_by_time() -> TResult<()> {
+        let nan = f64::NAN;
+        let secs = [0, 30, 60, 200, 210, 400];
+        let index: Vec<DateTime> = secs
+            .into_iter()
+            .map(|s| DateTime::new(s * 1_000_000_000))
+            .collect();
+        let v = vec![1., 2., nan, 4., 5., 6.];
+        let delta = TimeDelta::parse("1m")?;
+        let res: Vec<_> = v.vshift_by_time(&index, delta, None)?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, 1., nan, nan, 5.], None);
+        let tolerance = Some(TimeDelta::parse("100s")?);
+        let res: Vec<_> = v.vshift_by_time(&index, delta, tolerance)?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![nan, nan, 1., nan, nan, nan], None);
+        // lead by a negative delta
+        let delta = TimeDelta::parse("-30s")?;
+        let res: Vec<_> = v.vshift_by_time(&index, delta, None)?.collect();
+        assert_vec1d_equal_numeric(&res, &vec![2., nan, nan, 5., 5., 6.], None);
+        assert!(v.vshift_by_time(&index[..2].to_vec(), delta, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort() {
+        let nan = f64::NAN;
+        let v = vec![2., nan, 1., 2., nan, 0.];
+        let idx: Vec<usize> = v.vargsort(false, NanPlacement::Bottom);
+        assert_eq!(idx, vec![5, 2, 0, 3, 1, 4]);
+        let idx: Vec<usize> = v.vargsort(true, NanPlacement::Top);
+        assert_eq!(idx, vec![1, 4, 0, 3, 2, 5]);
+        let idx: Vec<usize> = v.vargsort(false, NanPlacement::Keep);
+        assert_eq!(idx, vec![5, 1, 2, 0, 4, 3]);
+        let res: Vec<f64> = v.vsort(false, NanPlacement::Keep);
+        assert_vec1d_equal_numeric(&res, &vec![0., nan, 1., 2., nan, 2.], None);
+        let res: Vec<f64> = v.vsort(true, NanPlacement::Bottom);
+        assert_vec1d_equal_numeric(&res, &vec![2., 2., 1., 0., nan, nan], None);
+        let empty: Vec<f64> = vec![];
+        let res: Vec<f64> = empty.vsort(false, NanPlacement::Top);
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn test_searchsorted() {
+        let nan = f64::NAN;
+        let v = vec![1., 2., 2., 3., nan];
+        let values = vec![0.5, 1., 2., 2.5, 3., 4., nan];
+        let idx: Vec<usize> = v.vsearchsorted(&values, SearchSide::Left);
+        assert_eq!(idx, vec![0, 0, 1, 3, 3, 4, 4]);
+        let idx: Vec<usize> = v.vsearchsorted(&values, SearchSide::Right);
+        assert_eq!(idx, vec![0, 1, 3, 3, 4, 4, 5]);
+        let empty: Vec<f64> = vec![];
+        let idx: Vec<usize> = empty.vsearchsorted(&values, SearchSide::Right);
+        assert_eq!(idx, vec![0; 7]);
+    }
+
     #[test]
     fn test_partition() {
         let v = vec![1, 3, 5, 1, 5, 6, 7, 32, 1];
@@ -1,5 +1,54 @@
 use tea_core::prelude::*;
 
+/// Incremental sums used to compute the pearson correlation of pairs of values.
+#[derive(Default, Clone, Copy)]
+struct CorrSums {
+    n: usize,
+    sum_a: f64,
+    sum2_a: f64,
+    sum_b: f64,
+    sum2_b: f64,
+    sum_ab: f64,
+}
+
+impl CorrSums {
+    #[inline]
+    fn add(&mut self, va: f64, vb: f64) {
+        self.n += 1;
+        self.sum_a += va;
+        self.sum2_a += va * va;
+        self.sum_b += vb;
+        self.sum2_b += vb * vb;
+        self.sum_ab += va * vb;
+    }
+
+    #[inline]
+    fn remove(&mut self, va: f64, vb: f64) {
+        self.n -= 1;
+        self.sum_a -= va;
+        self.sum2_a -= va * va;
+        self.sum_b -= vb;
+        self.sum2_b -= vb * vb;
+        self.sum_ab -= va * vb;
+    }
+
+    #[inline]
+    fn corr(&self, min_periods: usize) -> f64 {
+        if self.n < min_periods.max(2) {
+            return f64::NAN;
+        }
+        let n = self.n.f64();
+        let (mean_a, mean_b) = (self.sum_a / n, self.sum_b / n);
+        let var_a = self.sum2_a / n - mean_a.powi(2);
+        let var_b = self.sum2_b / n - mean_b.powi(2);
+        if (var_a > EPS) & (var_b > EPS) {
+            (self.sum_ab / n - mean_a * mean_b) / (var_a * var_b).sqrt()
+        } else {
+            f64::NAN
+        }
+    }
+}
+
 /// Trait for performing rolling binary operations on valid elements in vectors.
 ///
 /// This trait provides methods for calculating rolling covariance and correlation
@@ -147,6 +196,114 @@ pub trait RollingValidBinary<T: IsNone>: Vec1View<T> {
             out,
         )
     }
+
+    /// Finds the lag with the maximum cross-correlation between two vectors within a
+    /// rolling window (lead-lag detection).
+    ///
+    /// For a lag `l >= 0`, `self[i]` is paired with `other[i + l]`, so a positive lag
+    /// means `self` leads `other` by `l` periods; for a negative lag `self[i + |l|]` is
+    /// paired with `other[i]`. Only pairs with both elements inside the window are used,
+    /// and the correlation of each lag is updated incrementally as the window moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other vector to calculate cross-correlation with.
+    /// * `window` - The size of the rolling window.
+    /// * `max_lag` - The lags in `[-max_lag, max_lag]` are searched.
+    /// * `min_periods` - The minimum number of valid pairs required for a lag to have a
+    ///   correlation value.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(lag, correlation)` tuples, both NaN if no lag has enough valid pairs.
+    /// If several lags have the same correlation, the one with the smallest absolute
+    /// value (and then the negative one) is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of the two vectors are not equal.
+    #[no_out]
+    fn ts_vxcorr_argmax<O: Vec1<(U, U)>, U, V2: Vec1View<T2>, T2: IsNone>(
+        &self,
+        other: &V2,
+        window: usize,
+        max_lag: usize,
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        T2::Inner: Number,
+        f64: Cast<U>,
+    {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "the lengths of the two vectors must be equal"
+        );
+        let min_periods = min_periods.unwrap_or(window / 2).min(window);
+        // lag candidates ordered by absolute value, so the smallest lag wins on a tie, the
+        // window is clamped to the length when writing to `out`, so is the maximum lag
+        let max_lag = max_lag.min(window.min(self.len()).saturating_sub(1));
+        let lags: Vec<isize> = std::iter::once(0)
+            .chain((1..=max_lag as isize).flat_map(|l| [-l, l]))
+            .collect();
+        let mut sums = vec![CorrSums::default(); lags.len()];
+        // the valid pair of `self[i]` and `other[j]`
+        let pair = |i: usize, j: usize| -> Option<(f64, f64)> {
+            // SAFETY: the absolute lags are less than the window (clamped to the length), so
+            // both indices of a pair are inside the current window and at most its end, which
+            // is less than the length of `self`, and the lengths of the vectors are equal
+            let (va, vb) = unsafe { (self.uget(i), other.uget(j)) };
+            (va.not_none() && vb.not_none()).then(|| (va.unwrap().f64(), vb.unwrap().f64()))
+        };
+        // indices of the pair for `lag` whose later element is at `end`
+        let idx_end = |lag: isize, end: usize| -> Option<(usize, usize)> {
+            let l = lag.unsigned_abs();
+            (end >= l).then(|| {
+                if lag >= 0 {
+                    (end - l, end)
+                } else {
+                    (end, end - l)
+                }
+            })
+        };
+        self.rolling2_apply_idx(
+            other,
+            window,
+            |start, end, _| {
+                for (s, &lag) in sums.iter_mut().zip(&lags) {
+                    if let Some((va, vb)) = idx_end(lag, end).and_then(|(i, j)| pair(i, j)) {
+                        s.add(va, vb);
+                    }
+                }
+                let (mut best_lag, mut best_corr) = (f64::NAN, f64::NAN);
+                for (s, &lag) in sums.iter().zip(&lags) {
+                    let corr = s.corr(min_periods);
+                    if corr.not_none() && (best_corr.is_none() || corr > best_corr) {
+                        (best_lag, best_corr) = (lag as f64, corr);
+                    }
+                }
+                if let Some(start) = start {
+                    // remove the pairs whose earlier element is at `start`
+                    for (s, &lag) in sums.iter_mut().zip(&lags) {
+                        let l = lag.unsigned_abs();
+                        let (i, j) = if lag >= 0 {
+                            (start, start + l)
+                        } else {
+                            (start + l, start)
+                        };
+                        if let Some((va, vb)) = pair(i, j) {
+                            s.remove(va, vb);
+                        }
+                    }
+                }
+                (best_lag.cast(), best_corr.cast())
+            },
+            out,
+        )
+    }
 }
 
 impl<T: IsNone, I: Vec1View<T>> RollingValidBinary<T> for I {}
@@ -188,4 +345,78 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_xcorr_argmax() {
+        let a: Vec<f64> = (0..40).map(|i| ((i * i) % 17) as f64).collect();
+        // `a` leads `b` by 2 periods
+        let b: Vec<f64> = (0..40)
+            .map(|i| if i >= 2 { a[i - 2] } else { f64::NAN })
+            .collect();
+        let res: Vec<(f64, f64)> = a.ts_vxcorr_argmax(&b, 10, 3, None);
+        for (lag, corr) in &res[..4] {
+            assert!(lag.is_nan() && corr.is_nan());
+        }
+        for (lag, corr) in &res[12..] {
+            assert_eq!(*lag, 2.);
+            assert!((corr - 1.).abs() < 1e-10);
+        }
+        let res: Vec<(f64, f64)> = b.ts_vxcorr_argmax(&a, 10, 3, None);
+        assert_eq!(res[39].0, -2.);
+        // compare with the correlation of the lagged slices
+        let c: Vec<f64> = (0..40).map(|i| ((i * 7) % 11) as f64).collect();
+        let res: Vec<(f64, f64)> = a.ts_vxcorr_argmax(&c, 10, 2, Some(5));
+        let end = 30;
+        let expect = (-2isize..=2)
+            .map(|l| {
+                let (x, y) = if l >= 0 {
+                    (
+                        &a[end - 9..=end - l as usize],
+                        &c[end - 9 + l as usize..=end],
+                    )
+                } else {
+                    (
+                        &a[end - 9 + (-l) as usize..=end],
+                        &c[end - 9..=end - (-l) as usize],
+                    )
+                };
+                (l as f64, x.titer().vcorr_pearson(y.titer(), 5))
+            })
+            .fold((f64::NAN, f64::NEG_INFINITY), |acc, v| {
+                if v.1 > acc.1 { v } else { acc }
+            });
+        assert_eq!(res[end].0, expect.0);
+        assert!((res[end].1 - expect.1).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_xcorr_argmax_long_window() {
+        // the window and the lags are longer than the vectors
+        let a = vec![1., 3., 2., 5., 4.];
+        let b = vec![2., 1., 4., 3., 6.];
+        let expect: Vec<(f64, f64)> = a.ts_vxcorr_argmax(&b, 10, 20, Some(2));
+        let mut out = Vec::<(f64, f64)>::uninit(a.len());
+        a.ts_vxcorr_argmax_to::<Vec<(f64, f64)>, _, _, _>(
+            &b,
+            10,
+            20,
+            Some(2),
+            Some(Vec::<(f64, f64)>::uninit_ref_mut(&mut out)),
+        );
+        let res: Vec<(f64, f64)> = unsafe { out.assume_init() };
+        assert_eq!(res.len(), expect.len());
+        for ((l1, c1), (l2, c2)) in res.iter().zip(&expect) {
+            assert!(l1 == l2 || (l1.is_nan() && l2.is_nan()));
+            assert!((c1 - c2).abs() < 1e-10 || (c1.is_nan() && c2.is_nan()));
+        }
+        // `b[i] = a[i + 1] - 1`, so `b` leads `a` by one period
+        assert_eq!(expect[4].0, -1.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_xcorr_argmax_len_mismatch() {
+        let a = vec![1., 2., 3., 4.];
+        let _: Vec<(f64, f64)> = a.ts_vxcorr_argmax(&vec![1., 2.], 3, 1, None);
+    }
 }
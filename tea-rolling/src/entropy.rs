@@ -0,0 +1,423 @@
+use tea_core::prelude::*;
+
+/// Returns the index of the bin that `v` falls into.
+///
+/// The semantics follow `MapValidBasic::vcut` with `add_bounds = true`: `bins` are
+/// sorted bin edges and -∞ / +∞ are added as the outermost edges, so there are
+/// `bins.len() + 1` bins in total.
+#[inline]
+fn bin_idx(bins: &[f64], v: f64, right: bool) -> usize {
+    if right {
+        // intervals are (e_{i-1}, e_i]
+        bins.partition_point(|e| *e < v)
+    } else {
+        // intervals are [e_{i-1}, e_i)
+        bins.partition_point(|e| *e <= v)
+    }
+}
+
+/// `c * ln(c)`, with `0 * ln(0) = 0`.
+#[inline]
+fn xlnx(c: usize) -> f64 {
+    if c > 1 { c.f64() * c.f64().ln() } else { 0. }
+}
+
+/// Shannon entropy (in nats) of a histogram with `n` observations, where
+/// `sum_clnc` is the sum of `c * ln(c)` over all bin counts `c`.
+#[inline]
+fn hist_entropy(n: usize, sum_clnc: f64) -> f64 {
+    let n_f64 = n.f64();
+    n_f64.ln() - sum_clnc / n_f64
+}
+
+/// An incrementally updated histogram which keeps track of `Σ c * ln(c)`,
+/// so that the entropy can be calculated in O(1) after each update.
+struct Hist {
+    counts: Vec<usize>,
+    sum_clnc: f64,
+}
+
+impl Hist {
+    #[inline]
+    fn new(n_bins: usize) -> Self {
+        Self {
+            counts: vec![0; n_bins],
+            sum_clnc: 0.,
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, idx: usize) {
+        let c = &mut self.counts[idx];
+        self.sum_clnc += xlnx(*c + 1) - xlnx(*c);
+        *c += 1;
+    }
+
+    #[inline]
+    fn remove(&mut self, idx: usize) {
+        let c = &mut self.counts[idx];
+        self.sum_clnc += xlnx(*c - 1) - xlnx(*c);
+        *c -= 1;
+    }
+}
+
+/// Counts, for each of the first `n_templates` templates of length `m`, the number of
+/// templates whose Chebyshev distance to it is not greater than `r` (self match included).
+fn template_matches(x: &[f64], m: usize, r: f64, n_templates: usize) -> Vec<usize> {
+    let mut counts = vec![1; n_templates];
+    for i in 0..n_templates {
+        for j in i + 1..n_templates {
+            if x[i..i + m]
+                .iter()
+                .zip(&x[j..j + m])
+                .fold(0., |acc, (a, b)| (a - b).abs().max(acc))
+                <= r
+            {
+                counts[i] += 1;
+                counts[j] += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Trait for rolling window entropy and information-theoretic features on valid (non-None) elements.
+pub trait RollingValidEntropy<T: IsNone>: Vec1View<T> {
+    /// Calculates the rolling Shannon entropy (in nats) of the discretized values within a window.
+    ///
+    /// Values are discretized with the same semantics as `MapValidBasic::vcut` when
+    /// `add_bounds` is true, so `bins.len() + 1` bins are used. The histogram is updated
+    /// incrementally as the window moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The size of the rolling window.
+    /// * `bins` - The sorted bin edges used to discretize the values.
+    /// * `right` - If true, intervals are closed on the right. If false, intervals are closed on the left.
+    /// * `min_periods` - The minimum number of observations in window required to have a value.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the rolling entropy values.
+    #[no_out]
+    fn ts_ventropy<O: Vec1<U>, U>(
+        &self,
+        window: usize,
+        bins: &[f64],
+        right: bool,
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        f64: Cast<U>,
+    {
+        let min_periods = min_periods.unwrap_or(window / 2).min(window).max(1);
+        let mut hist = Hist::new(bins.len() + 1);
+        let mut n = 0;
+        self.rolling_apply(
+            window,
+            move |v_rm, v| {
+                if v.not_none() {
+                    n += 1;
+                    hist.add(bin_idx(bins, v.unwrap().f64(), right));
+                }
+                let res = if n >= min_periods {
+                    hist_entropy(n, hist.sum_clnc)
+                } else {
+                    f64::NAN
+                };
+                if let Some(v_rm) = v_rm
+                    && v_rm.not_none()
+                {
+                    n -= 1;
+                    hist.remove(bin_idx(bins, v_rm.unwrap().f64(), right));
+                }
+                res.cast()
+            },
+            out,
+        )
+    }
+
+    /// Calculates the rolling approximate entropy (ApEn) of valid elements within a window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The size of the rolling window.
+    /// * `m` - The embedding dimension (length of the compared templates).
+    /// * `r` - The tolerance, as a multiple of the standard deviation of the valid values in the window.
+    /// * `min_periods` - The minimum number of observations in window required to have a value.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the rolling approximate entropy values.
+    ///
+    /// # Notes
+    ///
+    /// The computation of each window is O(window²), self matches are included as in Pincus (1991).
+    #[no_out]
+    fn ts_vapprox_entropy<O: Vec1<U>, U: Clone>(
+        &self,
+        window: usize,
+        m: usize,
+        r: f64,
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        f64: Cast<U>,
+    {
+        let min_periods = min_periods.unwrap_or(window / 2).min(window).max(m + 2);
+        self.rolling_custom(
+            window,
+            |arr| {
+                let x: Vec<f64> = arr
+                    .titer()
+                    .filter(IsNone::not_none)
+                    .map(|v| v.unwrap().f64())
+                    .collect();
+                let n = x.len();
+                if n < min_periods {
+                    return f64::NAN.cast();
+                }
+                let tol = r * x.titer().vstd(2);
+                let phi = |m: usize| {
+                    let n_templates = n - m + 1;
+                    template_matches(&x, m, tol, n_templates)
+                        .into_iter()
+                        .map(|c| (c.f64() / n_templates.f64()).ln())
+                        .vmean()
+                };
+                (phi(m) - phi(m + 1)).cast()
+            },
+            out,
+        )
+    }
+
+    /// Calculates the rolling sample entropy (SampEn) of valid elements within a window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The size of the rolling window.
+    /// * `m` - The embedding dimension (length of the compared templates).
+    /// * `r` - The tolerance, as a multiple of the standard deviation of the valid values in the window.
+    /// * `min_periods` - The minimum number of observations in window required to have a value.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the rolling sample entropy values. The result is NaN if
+    /// no template matches are found in the window.
+    ///
+    /// # Notes
+    ///
+    /// The computation of each window is O(window²), self matches are excluded as in
+    /// Richman & Moorman (2000).
+    #[no_out]
+    fn ts_vsample_entropy<O: Vec1<U>, U: Clone>(
+        &self,
+        window: usize,
+        m: usize,
+        r: f64,
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        f64: Cast<U>,
+    {
+        let min_periods = min_periods.unwrap_or(window / 2).min(window).max(m + 2);
+        self.rolling_custom(
+            window,
+            |arr| {
+                let x: Vec<f64> = arr
+                    .titer()
+                    .filter(IsNone::not_none)
+                    .map(|v| v.unwrap().f64())
+                    .collect();
+                let n = x.len();
+                if n < min_periods {
+                    return f64::NAN.cast();
+                }
+                let tol = r * x.titer().vstd(2);
+                // use the same number of templates for both m and m + 1,
+                // and exclude self matches
+                let n_templates = n - m;
+                let b = template_matches(&x, m, tol, n_templates).vsum().unwrap() - n_templates;
+                let a = template_matches(&x, m + 1, tol, n_templates)
+                    .vsum()
+                    .unwrap()
+                    - n_templates;
+                if a == 0 || b == 0 {
+                    f64::NAN.cast()
+                } else {
+                    (-(a.f64() / b.f64()).ln()).cast()
+                }
+            },
+            out,
+        )
+    }
+}
+
+/// Trait for rolling window information-theoretic features between two vectors.
+pub trait RollingValidEntropyBinary<T: IsNone>: Vec1View<T> {
+    /// Calculates the rolling mutual information (in nats) between two discretized vectors.
+    ///
+    /// Both vectors are discretized with the same semantics as `MapValidBasic::vcut` when
+    /// `add_bounds` is true and intervals are closed on the right. The marginal and joint histograms are updated incrementally,
+    /// and only pairs where both values are valid are used.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other vector to calculate mutual information with.
+    /// * `window` - The size of the rolling window.
+    /// * `bins` - The sorted bin edges used to discretize the values of `self`.
+    /// * `other_bins` - The sorted bin edges used to discretize the values of `other`.
+    /// * `min_periods` - The minimum number of observations in window required to have a value.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the rolling mutual information values.
+    #[no_out]
+    fn ts_vmutual_info<O: Vec1<U>, U, V2: Vec1View<T2>, T2: IsNone>(
+        &self,
+        other: &V2,
+        window: usize,
+        bins: &[f64],
+        other_bins: &[f64],
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        T2::Inner: Number,
+        f64: Cast<U>,
+    {
+        let min_periods = min_periods.unwrap_or(window / 2).min(window).max(1);
+        let n_bins_b = other_bins.len() + 1;
+        let mut hist_a = Hist::new(bins.len() + 1);
+        let mut hist_b = Hist::new(n_bins_b);
+        let mut hist_ab = Hist::new((bins.len() + 1) * n_bins_b);
+        let mut n = 0;
+        self.rolling2_apply(
+            other,
+            window,
+            |remove_values, (va, vb)| {
+                if va.not_none() && vb.not_none() {
+                    n += 1;
+                    let ia = bin_idx(bins, va.unwrap().f64(), true);
+                    let ib = bin_idx(other_bins, vb.unwrap().f64(), true);
+                    hist_a.add(ia);
+                    hist_b.add(ib);
+                    hist_ab.add(ia * n_bins_b + ib);
+                };
+                let res = if n >= min_periods {
+                    // I(A; B) = H(A) + H(B) - H(A, B)
+                    let mi = hist_entropy(n, hist_a.sum_clnc) + hist_entropy(n, hist_b.sum_clnc)
+                        - hist_entropy(n, hist_ab.sum_clnc);
+                    mi.max(0.)
+                } else {
+                    f64::NAN
+                };
+                if let Some((va, vb)) = remove_values
+                    && va.not_none()
+                    && vb.not_none()
+                {
+                    n -= 1;
+                    let ia = bin_idx(bins, va.unwrap().f64(), true);
+                    let ib = bin_idx(other_bins, vb.unwrap().f64(), true);
+                    hist_a.remove(ia);
+                    hist_b.remove(ib);
+                    hist_ab.remove(ia * n_bins_b + ib);
+                }
+                res.cast()
+            },
+            out,
+        )
+    }
+}
+
+impl<T: IsNone, I: Vec1View<T>> RollingValidEntropy<T> for I {}
+impl<T: IsNone, I: Vec1View<T>> RollingValidEntropyBinary<T> for I {}
+
+#[cfg(test)]
+mod tests {
+    use tea_core::testing::assert_vec1d_equal_numeric;
+
+    use super::*;
+
+    fn naive_entropy(arr: &[f64], bins: &[f64]) -> f64 {
+        let valid: Vec<_> = arr.iter().copied().filter(|v| !v.is_nan()).collect();
+        let mut counts = vec![0; bins.len() + 1];
+        valid
+            .iter()
+            .for_each(|v| counts[bin_idx(bins, *v, true)] += 1);
+        let n = valid.len().f64();
+        counts.into_iter().filter(|c| *c > 0).fold(0., |acc, c| {
+            let p = c.f64() / n;
+            acc - p * p.ln()
+        })
+    }
+
+    #[test]
+    fn test_entropy() {
+        let data = vec![0.1, -0.3, 0.5, f64::NAN, 1.2, -0.8, 0.3, 0.2, -1.5, 0.9];
+        let bins = [-0.5, 0., 0.5];
+        let res: Vec<f64> = data.ts_ventropy(4, &bins, true, Some(1));
+        let expect: Vec<f64> = data
+            .rolling_custom(4, |arr| naive_entropy(arr, &bins), None)
+            .unwrap();
+        assert_vec1d_equal_numeric(&res, &expect, Some(1e-12));
+        // all values in the same bin
+        let res: Vec<f64> = vec![1., 2., 3.].ts_ventropy(2, &bins, true, Some(1));
+        assert_eq!(res, vec![0., 0., 0.]);
+    }
+
+    #[test]
+    fn test_approx_and_sample_entropy() {
+        let data = vec![1.; 10];
+        let res: Vec<f64> = data.ts_vapprox_entropy(5, 2, 0.2, None);
+        assert_vec1d_equal_numeric(
+            &res,
+            &vec![f64::NAN, f64::NAN, f64::NAN, 0., 0., 0., 0., 0., 0., 0.],
+            None,
+        );
+        let res: Vec<f64> = data.ts_vsample_entropy(5, 2, 0.2, None);
+        assert_vec1d_equal_numeric(
+            &res,
+            &vec![f64::NAN, f64::NAN, f64::NAN, 0., 0., 0., 0., 0., 0., 0.],
+            None,
+        );
+        // a regular series is more predictable than an irregular one
+        let regular: Vec<f64> = (0..30).map(|i| (i % 3) as f64).collect();
+        let irregular = vec![
+            3., 1., 4., 1., 5., 9., 2., 6., 5., 3., 5., 8., 9., 7., 9., 3., 2., 3., 8., 4., 6., 2.,
+            6., 4., 3., 3., 8., 3., 2., 7.,
+        ];
+        let apen_r: Vec<f64> = regular.ts_vapprox_entropy(30, 2, 0.2, None);
+        let apen_i: Vec<f64> = irregular.ts_vapprox_entropy(30, 2, 0.2, None);
+        assert!(apen_r[29] < apen_i[29]);
+        let sampen_r: Vec<f64> = regular.ts_vsample_entropy(30, 2, 0.2, None);
+        assert!(sampen_r[29].abs() < EPS);
+    }
+
+    #[test]
+    fn test_mutual_info() {
+        let data = vec![0.1, -0.3, 0.5, 0.7, 1.2, -0.8, 0.3, 0.2, -1.5, 0.9];
+        let bins = [-0.5, 0., 0.5];
+        // mutual information with itself equals to its entropy
+        let mi: Vec<f64> = data.ts_vmutual_info(&data, 4, &bins, &bins, Some(1));
+        let entropy: Vec<f64> = data.ts_ventropy(4, &bins, true, Some(1));
+        assert_vec1d_equal_numeric(&mi, &entropy, Some(1e-12));
+        // independent values in the same bin have no mutual information
+        let other = vec![1.; 10];
+        let mi: Vec<f64> = data.ts_vmutual_info(&other, 4, &bins, &bins, Some(1));
+        assert_vec1d_equal_numeric(&mi, &vec![0.; 10], Some(1e-12));
+    }
+}
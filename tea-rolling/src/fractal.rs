@@ -0,0 +1,278 @@
+use tea_core::prelude::*;
+
+use crate::reg::ols;
+
+/// Enum representing different methods for estimating the Hurst exponent.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum HurstMethod {
+    /// Rescaled range (R/S) analysis.
+    #[default]
+    RS,
+    /// Detrended fluctuation analysis.
+    DFA,
+}
+
+/// The smallest sub-period size used to estimate the Hurst exponent.
+const HURST_MIN_SIZE: usize = 4;
+
+/// Returns the sub-period sizes used to estimate the Hurst exponent of a series with
+/// length `n`, sizes are doubled from [`HURST_MIN_SIZE`] up to `n / 2`.
+#[inline]
+fn hurst_sizes(n: usize) -> impl Iterator<Item = usize> {
+    std::iter::successors(Some(HURST_MIN_SIZE), |s| Some(s * 2)).take_while(move |s| *s <= n / 2)
+}
+
+/// Averaged rescaled range of the non-overlapping sub-periods with length `size`.
+fn rescaled_range(x: &[f64], size: usize) -> f64 {
+    let (mut rs_sum, mut count) = (0., 0usize);
+    for chunk in x.chunks_exact(size) {
+        let mean = chunk.titer().vmean();
+        let (mut cum, mut max, mut min, mut sum2) = (0., 0., 0., 0.);
+        for v in chunk {
+            let dev = v - mean;
+            cum += dev;
+            max = cum.max(max);
+            min = cum.min(min);
+            sum2 += dev * dev;
+        }
+        let std = (sum2 / size.f64()).sqrt();
+        if std > EPS {
+            rs_sum += (max - min) / std;
+            count += 1;
+        }
+    }
+    if count > 0 {
+        rs_sum / count.f64()
+    } else {
+        f64::NAN
+    }
+}
+
+/// Root mean square fluctuation of the linearly detrended profile `y`, computed over the
+/// non-overlapping sub-periods with length `size`.
+fn dfa_fluctuation(y: &[f64], size: usize) -> f64 {
+    let (mut sse, mut count) = (0., 0usize);
+    for chunk in y.chunks_exact(size) {
+        let (alpha, beta) = ols(chunk.iter().enumerate().map(|(t, v)| (t.f64(), *v)));
+        sse += chunk
+            .iter()
+            .enumerate()
+            .fold(0., |acc, (t, v)| acc + (v - alpha - beta * t.f64()).powi(2));
+        count += size;
+    }
+    if count > 0 {
+        (sse / count.f64()).sqrt()
+    } else {
+        f64::NAN
+    }
+}
+
+/// Estimates the Hurst exponent of `x` as the slope of the log-log fit between the
+/// sub-period sizes and the fluctuation measure of the chosen method.
+fn hurst(x: &[f64], method: HurstMethod) -> f64 {
+    let n = x.len();
+    match method {
+        HurstMethod::RS => {
+            ols(hurst_sizes(n).filter_map(|s| {
+                let rs = rescaled_range(x, s);
+                (rs > 0.).then(|| (s.f64().ln(), rs.ln()))
+            }))
+            .1
+        },
+        HurstMethod::DFA => {
+            let mean = x.titer().vmean();
+            let mut cum = 0.;
+            let profile: Vec<f64> = x
+                .iter()
+                .map(|v| {
+                    cum += v - mean;
+                    cum
+                })
+                .collect();
+            ols(hurst_sizes(n).filter_map(|s| {
+                let f = dfa_fluctuation(&profile, s);
+                (f > 0.).then(|| (s.f64().ln(), f.ln()))
+            }))
+            .1
+        },
+    }
+}
+
+/// Estimates the Higuchi fractal dimension of `x` with time intervals `1..=kmax`.
+fn higuchi_fd(x: &[f64], kmax: usize) -> f64 {
+    let n = x.len();
+    ols((1..=kmax).filter_map(|k| {
+        let (mut l_sum, mut count) = (0., 0usize);
+        for m in 0..k {
+            let n_step = (n - 1 - m) / k;
+            if n_step == 0 {
+                continue;
+            }
+            let length =
+                (1..=n_step).fold(0., |acc, i| acc + (x[m + i * k] - x[m + (i - 1) * k]).abs());
+            // normalized curve length of the sub series starting at m
+            l_sum += length * (n - 1).f64() / (n_step * k).f64() / k.f64();
+            count += 1;
+        }
+        (count > 0 && l_sum > 0.).then(|| ((1. / k.f64()).ln(), (l_sum / count.f64()).ln()))
+    }))
+    .1
+}
+
+/// Trait for rolling window fractal features (Hurst exponent, fractal dimension)
+/// on valid (non-None) elements.
+pub trait RollingValidFractal<T: IsNone>: Vec1View<T> {
+    /// Calculates the rolling Hurst exponent of valid elements within a window.
+    ///
+    /// The series is expected to be increments (e.g. returns). It is split into
+    /// non-overlapping sub-periods whose sizes are doubled from 4 up to half of the
+    /// valid elements in the window, and the Hurst exponent is the slope of the
+    /// log-log regression between the sub-period size and the fluctuation measure.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The size of the rolling window.
+    /// * `method` - The method used to measure the fluctuation of each sub-period:
+    ///     - [`HurstMethod::RS`]: the averaged rescaled range.
+    ///     - [`HurstMethod::DFA`]: the root mean square of the linearly detrended profile.
+    /// * `min_periods` - The minimum number of observations in window required to have a value,
+    ///   at least 16 observations are needed to get two sub-period sizes.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the rolling Hurst exponent values.
+    #[no_out]
+    fn ts_vhurst<O: Vec1<U>, U: Clone>(
+        &self,
+        window: usize,
+        method: HurstMethod,
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        f64: Cast<U>,
+    {
+        let min_periods = min_periods
+            .unwrap_or(window / 2)
+            .min(window)
+            .max(4 * HURST_MIN_SIZE);
+        self.rolling_custom(
+            window,
+            |arr| {
+                let x: Vec<f64> = arr
+                    .titer()
+                    .filter(IsNone::not_none)
+                    .map(|v| v.unwrap().f64())
+                    .collect();
+                if x.len() >= min_periods {
+                    hurst(&x, method).cast()
+                } else {
+                    f64::NAN.cast()
+                }
+            },
+            out,
+        )
+    }
+
+    /// Calculates the rolling Higuchi fractal dimension of valid elements within a window.
+    ///
+    /// The fractal dimension is the slope of the log-log regression between the normalized
+    /// curve length `L(k)` and `1 / k` for time intervals `k = 1..=kmax`.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The size of the rolling window.
+    /// * `kmax` - The maximum time interval, must be at least 2.
+    /// * `min_periods` - The minimum number of observations in window required to have a value.
+    /// * `out` - Optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the rolling fractal dimension values, which lie between 1 (a
+    /// smooth curve) and 2 (a curve that fills the plane).
+    #[no_out]
+    fn ts_vfractal_dim<O: Vec1<U>, U: Clone>(
+        &self,
+        window: usize,
+        kmax: usize,
+        min_periods: Option<usize>,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        f64: Cast<U>,
+    {
+        assert!(kmax >= 2, "kmax must be greater than 1");
+        let min_periods = min_periods.unwrap_or(window / 2).min(window).max(kmax + 1);
+        self.rolling_custom(
+            window,
+            |arr| {
+                let x: Vec<f64> = arr
+                    .titer()
+                    .filter(IsNone::not_none)
+                    .map(|v| v.unwrap().f64())
+                    .collect();
+                if x.len() >= min_periods {
+                    higuchi_fd(&x, kmax).cast()
+                } else {
+                    f64::NAN.cast()
+                }
+            },
+            out,
+        )
+    }
+}
+
+impl<T: IsNone, I: Vec1View<T>> RollingValidFractal<T> for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deterministic pseudo random series of increments in [-0.5, 0.5).
+    fn noise(n: usize) -> Vec<f64> {
+        let mut state = 42u64;
+        (0..n)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hurst() {
+        let x = noise(512);
+        // white noise has a hurst exponent around 0.5
+        let res: Vec<f64> = x.ts_vhurst(512, HurstMethod::DFA, None);
+        assert_eq!(res[..255].titer().count_valid(), 0);
+        assert!((res[511] - 0.5).abs() < 0.1);
+        let res: Vec<f64> = x.ts_vhurst(512, HurstMethod::RS, None);
+        assert!((res[511] - 0.5).abs() < 0.15);
+        // a trending series is persistent
+        let trend: Vec<f64> = (0..512).map(|i| (i as f64 / 50.).sin() + 1.).collect();
+        let res: Vec<f64> = trend.ts_vhurst(512, HurstMethod::DFA, None);
+        assert!(res[511] > 0.9);
+        // not enough observations
+        let res: Vec<f64> = x[..10].to_vec().ts_vhurst(10, HurstMethod::RS, Some(1));
+        assert_eq!(res.titer().count_valid(), 0);
+    }
+
+    #[test]
+    fn test_fractal_dim() {
+        // a straight line has a fractal dimension of 1
+        let line: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let res: Vec<f64> = line.ts_vfractal_dim(50, 8, None);
+        assert_eq!(res[..24].titer().count_valid(), 0);
+        assert!((res[99] - 1.).abs() < 1e-10);
+        // white noise has a fractal dimension around 2
+        let res: Vec<f64> = noise(500).ts_vfractal_dim(500, 8, None);
+        assert!((res[499] - 2.).abs() < 0.1);
+    }
+}
@@ -1,6 +1,8 @@
 mod binary;
 mod cmp;
+mod entropy;
 mod features;
+mod fractal;
 mod norm;
 mod reg;
 
@@ -15,6 +17,8 @@ extern crate tea_macros;
 
 pub use binary::RollingValidBinary;
 pub use cmp::{RollingCmp, RollingValidCmp};
+pub use entropy::{RollingValidEntropy, RollingValidEntropyBinary};
 pub use features::{RollingFeature, RollingValidFeature};
+pub use fractal::{HurstMethod, RollingValidFractal};
 pub use norm::RollingValidNorm;
 pub use reg::{RollingValidReg, RollingValidRegBinary};
@@ -200,7 +200,7 @@ mod tests {
         let res: Vec<f64> = data.ts_vzscore(4, None);
         let expect = vec![
             f64::NAN,
-            0.707107,
+            std::f64::consts::FRAC_1_SQRT_2,
             1.0,
             f64::NAN,
             1.091089,
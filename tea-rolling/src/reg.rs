@@ -1,4 +1,35 @@
 use tea_core::prelude::*;
+
+/// Fits a simple ordinary least squares regression `y = α + β x` on the given `(x, y)` pairs.
+///
+/// # Returns
+///
+/// A tuple `(alpha, beta)`, both are NaN if fewer than two pairs are given or
+/// all the `x` values are the same.
+pub(crate) fn ols<I: IntoIterator<Item = (f64, f64)>>(iter: I) -> (f64, f64) {
+    let (mut n, mut sum_x, mut sum_y, mut sum_x2, mut sum_xy) = (0usize, 0., 0., 0., 0.);
+    iter.into_iter().for_each(|(x, y)| {
+        n += 1;
+        sum_x += x;
+        sum_y += y;
+        sum_x2 += x * x;
+        sum_xy += x * y;
+    });
+    if n < 2 {
+        return (f64::NAN, f64::NAN);
+    }
+    let n_f64 = n.f64();
+    // β = (n Σxy - Σx Σy) / (n Σx² - (Σx)²)
+    let divisor = n_f64 * sum_x2 - sum_x.powi(2);
+    if divisor.abs() <= EPS {
+        return (f64::NAN, f64::NAN);
+    }
+    let beta = (n_f64 * sum_xy - sum_x * sum_y) / divisor;
+    // α = (Σy - β Σx) / n
+    let alpha = (sum_y - beta * sum_x) / n_f64;
+    (alpha, beta)
+}
+
 /// Trait for rolling window regression operations on valid (non-None) elements.
 pub trait RollingValidReg<T: IsNone>: Vec1View<T> {
     /// Calculates the rolling regression (predicted value) for valid elements within a window.
@@ -0,0 +1,160 @@
+use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+use crate::erf::{erfc, erfcinv};
+use crate::incomplete::{betainc, betaincinv, gammainc, gammaincc, gammaincinv};
+
+/// 1 / sqrt(2 * pi)
+const FRAC_1_SQRT_2PI: f64 = 0.398942280401432677939946059934381868;
+
+/// Probability density function of the standard normal distribution.
+#[inline]
+pub fn norm_pdf(x: f64) -> f64 {
+    FRAC_1_SQRT_2PI * (-0.5 * x * x).exp()
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+#[inline]
+pub fn norm_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x * FRAC_1_SQRT_2)
+}
+
+/// Survival function (`1 - cdf`) of the standard normal distribution.
+#[inline]
+pub fn norm_sf(x: f64) -> f64 {
+    0.5 * erfc(x * FRAC_1_SQRT_2)
+}
+
+/// Quantile function (inverse of the cdf) of the standard normal distribution.
+///
+/// Returns `-inf` for `p = 0`, `inf` for `p = 1` and NaN if `p` is outside of `[0, 1]`.
+#[inline]
+pub fn norm_ppf(p: f64) -> f64 {
+    -SQRT_2 * erfcinv(2. * p)
+}
+
+/// Cumulative distribution function of Student's t distribution with `df` degrees of
+/// freedom.
+pub fn t_cdf(x: f64, df: f64) -> f64 {
+    if x.is_nan() || df.is_nan() || df <= 0. {
+        return f64::NAN;
+    }
+    if x.is_infinite() {
+        return if x > 0. { 1. } else { 0. };
+    }
+    let x2 = x * x;
+    // the probability of |T| > |x|, computed with the argument which is not close to 1
+    let p = if x2 < df {
+        1. - betainc(0.5, 0.5 * df, x2 / (df + x2))
+    } else {
+        betainc(0.5 * df, 0.5, df / (df + x2))
+    };
+    if x > 0. { 1. - 0.5 * p } else { 0.5 * p }
+}
+
+/// Survival function (`1 - cdf`) of Student's t distribution with `df` degrees of freedom.
+#[inline]
+pub fn t_sf(x: f64, df: f64) -> f64 {
+    t_cdf(-x, df)
+}
+
+/// Quantile function (inverse of the cdf) of Student's t distribution with `df` degrees
+/// of freedom.
+///
+/// Returns `-inf` for `p = 0`, `inf` for `p = 1` and NaN if `p` is outside of `[0, 1]`.
+pub fn t_ppf(p: f64, df: f64) -> f64 {
+    if p.is_nan() || df.is_nan() || df <= 0. || !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+    if p == 0.5 {
+        return 0.;
+    }
+    // two sided tail probability
+    let p2 = 2. * p.min(1. - p);
+    let t = if p2 == 0. {
+        f64::INFINITY
+    } else if p2 < 0.5 {
+        let x = betaincinv(0.5 * df, 0.5, p2);
+        (df * (1. - x) / x).sqrt()
+    } else {
+        // t is small, solve for x^2 / (df + x^2) to keep the precision
+        let y = betaincinv(0.5, 0.5 * df, 1. - p2);
+        (df * y / (1. - y)).sqrt()
+    };
+    if p < 0.5 { -t } else { t }
+}
+
+/// Cumulative distribution function of the chi-squared distribution with `df` degrees of
+/// freedom.
+pub fn chi2_cdf(x: f64, df: f64) -> f64 {
+    if x.is_nan() || df.is_nan() || df <= 0. {
+        f64::NAN
+    } else if x <= 0. {
+        0.
+    } else {
+        gammainc(0.5 * df, 0.5 * x)
+    }
+}
+
+/// Survival function (`1 - cdf`) of the chi-squared distribution with `df` degrees of
+/// freedom.
+pub fn chi2_sf(x: f64, df: f64) -> f64 {
+    if x.is_nan() || df.is_nan() || df <= 0. {
+        f64::NAN
+    } else if x <= 0. {
+        1.
+    } else {
+        gammaincc(0.5 * df, 0.5 * x)
+    }
+}
+
+/// Quantile function (inverse of the cdf) of the chi-squared distribution with `df`
+/// degrees of freedom.
+///
+/// Returns `inf` for `p = 1` and NaN if `p` is outside of `[0, 1]`.
+#[inline]
+pub fn chi2_ppf(p: f64, df: f64) -> f64 {
+    2. * gammaincinv(0.5 * df, p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_close;
+
+    #[test]
+    fn test_norm() {
+        assert_eq!(norm_cdf(0.), 0.5);
+        assert_close(norm_cdf(1.96), 0.9750021048517795, 1e-14);
+        assert_close(norm_cdf(-10.), 7.619853024160527e-24, 1e-13);
+        assert_close(norm_sf(10.), 7.619853024160527e-24, 1e-13);
+        assert_close(norm_pdf(1.), 0.24197072451914337, 1e-14);
+        assert_close(norm_ppf(0.975), 1.959963984540054, 1e-14);
+        assert_close(norm_ppf(1e-10), -6.361340902404056, 1e-14);
+        assert_eq!(norm_ppf(0.5), 0.);
+        assert_eq!(norm_ppf(1.), f64::INFINITY);
+        assert!(norm_ppf(-0.1).is_nan());
+    }
+
+    #[test]
+    fn test_t() {
+        assert_eq!(t_cdf(0., 5.), 0.5);
+        assert_close(t_cdf(2., 5.), 0.9490302605850709, 1e-13);
+        assert_close(t_cdf(-0.1, 30.), 0.46050480589513558, 1e-13);
+        assert_close(t_sf(10., 3.), 0.0010641995292070508, 1e-12);
+        // the cauchy distribution
+        assert_close(t_cdf(1., 1.), 0.75, 1e-14);
+        assert_close(t_ppf(0.975, 10.), 2.2281388519862747, 1e-12);
+        assert_close(t_ppf(0.45, 4.), -0.13383036711194336, 1e-12);
+        assert_close(t_ppf(0.001, 2.5), -13.822193110865965, 1e-12);
+        assert_eq!(t_ppf(0.5, 3.), 0.);
+    }
+
+    #[test]
+    fn test_chi2() {
+        assert_close(chi2_cdf(3.84, 1.), 0.9499564787512949, 1e-13);
+        assert_close(chi2_sf(20., 5.), 0.0012497305630313754, 1e-12);
+        assert_eq!(chi2_cdf(-1., 3.), 0.);
+        assert_close(chi2_ppf(0.95, 1.), 3.841458820694126, 1e-12);
+        assert_close(chi2_ppf(0.05, 10.), 3.94029913611906, 1e-12);
+    }
+}
@@ -0,0 +1,174 @@
+use std::f64::consts::{FRAC_2_SQRT_PI, SQRT_2};
+
+use crate::incomplete::{gammainc, gammaincc};
+
+/// The maximum number of Halley iterations used by the inverse functions.
+const MAX_HALLEY_ITER: usize = 8;
+
+/// Computes the error function `erf(x) = 2 / sqrt(pi) * integral(exp(-t^2), 0, x)`.
+pub fn erf(x: f64) -> f64 {
+    if x.is_nan() {
+        x
+    } else if x.abs() < 1e-10 {
+        // erf(x) = 2x / sqrt(pi) * (1 - x^2 / 3 + ...)
+        FRAC_2_SQRT_PI * x
+    } else {
+        // erf(x) = P(1/2, x^2)
+        gammainc(0.5, x * x).copysign(x)
+    }
+}
+
+/// Computes the complementary error function `erfc(x) = 1 - erf(x)`.
+///
+/// The upper tail is computed directly so that it keeps full relative precision for
+/// large `x`.
+pub fn erfc(x: f64) -> f64 {
+    if x.is_nan() {
+        x
+    } else if x.abs() < 1e-10 {
+        1. - FRAC_2_SQRT_PI * x
+    } else if x < 0. {
+        1. + gammainc(0.5, x * x)
+    } else {
+        // erfc(x) = Q(1/2, x^2)
+        gammaincc(0.5, x * x)
+    }
+}
+
+/// Rational approximation of the standard normal quantile function by Acklam's
+/// algorithm, the relative error is less than 1.15e-9.
+fn ndtri_approx(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.383577518672690e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    };
+    if p < P_LOW {
+        tail((-2. * p.ln()).sqrt())
+    } else if p <= 1. - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.)
+    } else {
+        -tail((-2. * (1. - p).ln()).sqrt())
+    }
+}
+
+/// Refines the root `x` of `f(x) = 0` by Halley's method, where `f` is `erf(x) - y` or
+/// `y - erfc(x)` so that `f'(x) = 2 / sqrt(pi) * exp(-x^2)` and `f''(x) = -2x * f'(x)`.
+fn halley_refine(mut x: f64, f: impl Fn(f64) -> f64) -> f64 {
+    for _ in 0..MAX_HALLEY_ITER {
+        let u = f(x) / (FRAC_2_SQRT_PI * (-x * x).exp());
+        let step = u / (1. + x * u);
+        x -= step;
+        if step.abs() <= f64::EPSILON * x.abs() {
+            break;
+        }
+    }
+    x
+}
+
+/// Computes the inverse error function, i.e. the `x` such that `erf(x) = y`.
+///
+/// Returns `±inf` for `y = ±1` and NaN if `y` is outside of `[-1, 1]`.
+pub fn erfinv(y: f64) -> f64 {
+    if y.is_nan() || y.abs() > 1. {
+        f64::NAN
+    } else if y == 0. {
+        y
+    } else if y.abs() == 1. {
+        y * f64::INFINITY
+    } else if y.abs() > 0.5 {
+        // erfc keeps the precision when y is close to 1
+        let x = erfcinv(1. - y.abs());
+        x.copysign(y)
+    } else {
+        let x = ndtri_approx(0.5 * (1. + y)) / SQRT_2;
+        halley_refine(x, |x| erf(x) - y)
+    }
+}
+
+/// Computes the inverse complementary error function, i.e. the `x` such that
+/// `erfc(x) = y`.
+///
+/// Returns `inf` for `y = 0`, `-inf` for `y = 2` and NaN if `y` is outside of `[0, 2]`.
+pub fn erfcinv(y: f64) -> f64 {
+    if y.is_nan() || !(0. ..=2.).contains(&y) {
+        f64::NAN
+    } else if y == 0. {
+        f64::INFINITY
+    } else if y == 2. {
+        f64::NEG_INFINITY
+    } else if y > 1. {
+        -erfcinv(2. - y)
+    } else if y > 0.5 {
+        erfinv(1. - y)
+    } else {
+        let x = -ndtri_approx(0.5 * y) / SQRT_2;
+        halley_refine(x, |x| y - erfc(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_close;
+
+    #[test]
+    fn test_erf() {
+        assert_eq!(erf(0.), 0.);
+        assert_close(erf(0.5), 0.5204998778130465, 1e-14);
+        assert_close(erf(-1.5), -0.9661051464753108, 1e-14);
+        assert_close(erf(1e-12), 1.1283791670955126e-12, 1e-14);
+        assert_close(erfc(0.5), 0.4795001221869535, 1e-14);
+        assert_close(erfc(-1.), 1.8427007929497148, 1e-14);
+        assert_close(erfc(10.), 2.088487583762545e-45, 1e-13);
+        assert_eq!(erf(f64::INFINITY), 1.);
+        assert_eq!(erfc(f64::INFINITY), 0.);
+    }
+
+    #[test]
+    fn test_erfinv() {
+        for &y in &[-0.999, -0.7, -0.3, 1e-15, 0.1, 0.5, 0.9, 0.99999] {
+            assert_close(erf(erfinv(y)), y, 1e-14);
+        }
+        for &y in &[1e-300, 1e-20, 0.01, 0.4, 0.8, 1.3, 1.99] {
+            assert_close(erfc(erfcinv(y)), y, 1e-13);
+        }
+        assert_close(erfinv(0.5), 0.4769362762044699, 1e-14);
+        assert_close(erfcinv(1e-10), 4.572824967389486, 1e-14);
+        assert_eq!(erfinv(1.), f64::INFINITY);
+        assert_eq!(erfinv(-1.), f64::NEG_INFINITY);
+        assert!(erfinv(1.5).is_nan());
+    }
+}
@@ -0,0 +1,503 @@
+use std::f64::consts::PI;
+
+const MAXGAM: f64 = 171.624376956302725;
+const MAXLOG: f64 = 7.09782712893383996732E2;
+const MAXLGM: f64 = 2.556348e305;
+const MAXSTIR: f64 = 143.01608;
+/// sqrt(2 * pi)
+const SQRT_2PI: f64 = 2.50662827463100050242E0;
+/// log(pi)
+const LOGPI: f64 = 1.14472988584940017414;
+/// log(sqrt(2 * pi))
+const LS2PI: f64 = 0.91893853320467274178;
+const EULER: f64 = 0.5772156649015329;
+const BETA_ASYMP_FACTOR: f64 = 1e6;
+
+const GAMMA_P: [f64; 7] = [
+    1.60119522476751861407E-4,
+    1.19135147006586384913E-3,
+    1.04213797561761569935E-2,
+    4.76367800457137231464E-2,
+    2.07448227648435975150E-1,
+    4.94214826801497100753E-1,
+    9.99999999999999996796E-1,
+];
+const GAMMA_Q: [f64; 8] = [
+    -2.31581873324120129819E-5,
+    5.39605580493303397842E-4,
+    -4.45641913851797240494E-3,
+    1.18139785222060435552E-2,
+    3.58236398605498653373E-2,
+    -2.34591795718243348568E-1,
+    7.14304917030273074085E-2,
+    1.00000000000000000320E0,
+];
+const GAMMA_STIR: [f64; 5] = [
+    7.87311395793093628397E-4,
+    -2.29549961613378126380E-4,
+    -2.68132617805781232825E-3,
+    3.47222221605458667310E-3,
+    8.33333333333482257126E-2,
+];
+const GAMMA_A: [f64; 5] = [
+    8.11614167470508450300E-4,
+    -5.95061904284301438324E-4,
+    7.93650340457716943945E-4,
+    -2.77777777730099687205E-3,
+    8.33333333333331927722E-2,
+];
+const GAMMA_B: [f64; 6] = [
+    -1.37825152569120859100E3,
+    -3.88016315134637840924E4,
+    -3.31612992738871184744E5,
+    -1.16237097492762307383E6,
+    -1.72173700820839662146E6,
+    -8.53555664245765465627E5,
+];
+const GAMMA_C: [f64; 6] = [
+    -3.51815701436523470549E2,
+    -1.70642106651881159223E4,
+    -2.20528590553854454839E5,
+    -1.13933444367982507207E6,
+    -2.53252307177582951285E6,
+    -2.01889141433532773231E6,
+];
+
+/// Evaluates the polynomial with coefficients `coef` (highest degree first) at `x`.
+#[inline]
+fn polevl(x: f64, coef: &[f64]) -> f64 {
+    coef.iter().fold(0., |acc, c| acc * x + c)
+}
+
+/// Same as [`polevl`], but the coefficient of the highest degree is assumed to be 1
+/// and omitted from `coef`.
+#[inline]
+fn p1evl(x: f64, coef: &[f64]) -> f64 {
+    coef.iter().fold(1., |acc, c| acc * x + c)
+}
+
+/// Computes `sin(pi * x)` without the loss of precision of `pi * x` for large `x`.
+#[inline]
+fn sinpi(x: f64) -> f64 {
+    let (x, s) = if x < 0. { (-x, -1.) } else { (x, 1.) };
+    let r = x % 2.;
+    if r < 0.5 {
+        s * (PI * r).sin()
+    } else if r > 1.5 {
+        s * (PI * (r - 2.)).sin()
+    } else {
+        -s * (PI * (r - 1.)).sin()
+    }
+}
+
+/// Gamma function computed by Stirling's formula, valid for `33 <= x <= MAXGAM`.
+fn stirf(x: f64) -> f64 {
+    if x >= MAXGAM {
+        return f64::INFINITY;
+    }
+    let w = 1. / x;
+    let w = 1. + w * polevl(w, &GAMMA_STIR);
+    let y = x.exp();
+    let y = if x > MAXSTIR {
+        // avoid overflow in pow
+        let v = x.powf(0.5 * x - 0.25);
+        v * (v / y)
+    } else {
+        x.powf(x - 0.5) / y
+    };
+    SQRT_2PI * y * w
+}
+
+/// Computes the gamma function for a real argument.
+///
+/// Returns infinity at the poles (non-positive integers) and on overflow.
+pub fn gamma(x: f64) -> f64 {
+    if !x.is_finite() {
+        return x;
+    }
+    let q = x.abs();
+    if q > 33. {
+        return if x < 0. {
+            let mut p = q.floor();
+            if p == q {
+                return f64::INFINITY;
+            }
+            let sgngam = if (p as i64) & 1 == 0 { -1. } else { 1. };
+            let mut z = q - p;
+            if z > 0.5 {
+                p += 1.;
+                z = q - p;
+            }
+            let z = q * sinpi(z);
+            if z == 0. {
+                return sgngam * f64::INFINITY;
+            }
+            sgngam * PI / (z.abs() * stirf(q))
+        } else {
+            stirf(x)
+        };
+    }
+    let small = |x: f64, z: f64| {
+        if x == 0. {
+            f64::INFINITY
+        } else {
+            z / ((1. + EULER * x) * x)
+        }
+    };
+    let (mut x, mut z) = (x, 1.);
+    while x >= 3. {
+        x -= 1.;
+        z *= x;
+    }
+    while x < 0. {
+        if x > -1e-9 {
+            return small(x, z);
+        }
+        z /= x;
+        x += 1.;
+    }
+    while x < 2. {
+        if x < 1e-9 {
+            return small(x, z);
+        }
+        z /= x;
+        x += 1.;
+    }
+    if x == 2. {
+        return z;
+    }
+    x -= 2.;
+    z * polevl(x, &GAMMA_P) / polevl(x, &GAMMA_Q)
+}
+
+/// Natural logarithm of the gamma function for large `x`.
+fn lgam_large_x(x: f64) -> f64 {
+    let q = (x - 0.5) * x.ln() - x + LS2PI;
+    if x > 1e8 {
+        return q;
+    }
+    let p = 1. / (x * x);
+    q + ((7.9365079365079365079365e-4 * p - 2.7777777777777777777778e-3) * p
+        + 0.0833333333333333333333)
+        / x
+}
+
+/// Returns the natural logarithm of the absolute value of the gamma function together
+/// with the sign of the gamma function.
+fn lgam_sgn(x: f64) -> (f64, f64) {
+    if !x.is_finite() {
+        return (x, 1.);
+    }
+    if x < -34. {
+        let q = -x;
+        let (w, _) = lgam_sgn(q);
+        let mut p = q.floor();
+        if p == q {
+            return (f64::INFINITY, 1.);
+        }
+        let sign = if (p as i64) & 1 == 0 { -1. } else { 1. };
+        let mut z = q - p;
+        if z > 0.5 {
+            p += 1.;
+            z = p - q;
+        }
+        let z = q * sinpi(z);
+        if z == 0. {
+            return (f64::INFINITY, sign);
+        }
+        return (LOGPI - z.ln() - w, sign);
+    }
+    if x < 13. {
+        let (mut z, mut p, mut u) = (1., 0., x);
+        while u >= 3. {
+            p -= 1.;
+            u = x + p;
+            z *= u;
+        }
+        while u < 2. {
+            if u == 0. {
+                return (f64::INFINITY, 1.);
+            }
+            z /= u;
+            p += 1.;
+            u = x + p;
+        }
+        let sign = if z < 0. { -1. } else { 1. };
+        let z: f64 = z.abs();
+        if u == 2. {
+            return (z.ln(), sign);
+        }
+        p -= 2.;
+        let x = x + p;
+        let p = x * polevl(x, &GAMMA_B) / p1evl(x, &GAMMA_C);
+        return (z.ln() + p, sign);
+    }
+    if x > MAXLGM {
+        return (f64::INFINITY, 1.);
+    }
+    if x >= 1000. {
+        return (lgam_large_x(x), 1.);
+    }
+    let q = (x - 0.5) * x.ln() - x + LS2PI;
+    let p = 1. / (x * x);
+    (q + polevl(p, &GAMMA_A) / x, 1.)
+}
+
+/// Computes the natural logarithm of the absolute value of the gamma function.
+#[inline]
+pub fn lgamma(x: f64) -> f64 {
+    lgam_sgn(x).0
+}
+
+/// Returns the sign of the gamma function: `1` or `-1`, `0` at the poles and NaN for NaN.
+pub fn gammasgn(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 0. {
+        return 1.;
+    }
+    let fx = x.floor();
+    if x - fx == 0. {
+        0.
+    } else if (fx as i64) % 2 != 0 {
+        -1.
+    } else {
+        1.
+    }
+}
+
+/// Asymptotic expansion of `ln(|B(a, b)|)` for `a > ASYMP_FACTOR * max(|b|, 1)`,
+/// returned together with the sign of `B(a, b)`.
+fn lbeta_asymp(a: f64, b: f64) -> (f64, f64) {
+    let (mut r, sign) = lgam_sgn(b);
+    r -= b * a.ln();
+    r += b * (1. - b) / (2. * a);
+    r += b * (1. - b) * (1. - 2. * b) / (12. * a * a);
+    r += -b * b * (1. - b) * (1. - b) / (12. * a * a * a);
+    (r, sign)
+}
+
+/// Special case for the beta function at a negative integer argument `a`, the function
+/// is finite only when `b` is an integer such that `1 - a - b > 0`.
+fn beta_negint(a: i64, b: f64) -> f64 {
+    if b == (b as i64) as f64 && 1. - a as f64 - b > 0. {
+        let sgn = if (b as i64) % 2 == 0 { 1. } else { -1. };
+        sgn * beta(1. - a as f64 - b, b)
+    } else {
+        f64::INFINITY
+    }
+}
+
+fn lbeta_negint(a: i64, b: f64) -> f64 {
+    if b == (b as i64) as f64 && 1. - a as f64 - b > 0. {
+        lbeta(1. - a as f64 - b, b)
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Computes `B(a, b) = Gamma(a) * Gamma(b) / Gamma(a + b)` and its sign, the result is
+/// `Err` with the sign if the function overflows.
+fn beta_impl(a: f64, b: f64) -> Result<(f64, f64), f64> {
+    let (a, b) = if a.abs() < b.abs() { (b, a) } else { (a, b) };
+    if a.abs() > BETA_ASYMP_FACTOR * b.abs() && a > BETA_ASYMP_FACTOR {
+        // avoid loss of precision in lgamma(a + b) - lgamma(a)
+        let (y, sign) = lbeta_asymp(a, b);
+        return Ok((y.exp(), sign));
+    }
+    let y = a + b;
+    if y.abs() > MAXGAM || a.abs() > MAXGAM || b.abs() > MAXGAM {
+        let (ly, sy) = lgam_sgn(y);
+        let (lb, sb) = lgam_sgn(b);
+        let (la, sa) = lgam_sgn(a);
+        let sign = sy * sb * sa;
+        let y = la + (lb - ly);
+        return if y > MAXLOG {
+            Err(sign)
+        } else {
+            Ok((y.exp(), sign))
+        };
+    }
+    let (y, a, b) = (gamma(y), gamma(a), gamma(b));
+    if y == 0. {
+        return Err(1.);
+    }
+    let res = if (a.abs() - y.abs()).abs() > (b.abs() - y.abs()).abs() {
+        b / y * a
+    } else {
+        a / y * b
+    };
+    Ok((res.abs(), res.signum()))
+}
+
+/// Computes the beta function `B(a, b) = Gamma(a) * Gamma(b) / Gamma(a + b)`.
+pub fn beta(a: f64, b: f64) -> f64 {
+    if a <= 0. && a == a.floor() {
+        return if a == (a as i64) as f64 {
+            beta_negint(a as i64, b)
+        } else {
+            f64::INFINITY
+        };
+    }
+    if b <= 0. && b == b.floor() {
+        return if b == (b as i64) as f64 {
+            beta_negint(b as i64, a)
+        } else {
+            f64::INFINITY
+        };
+    }
+    match beta_impl(a, b) {
+        Ok((y, sign)) => sign * y,
+        Err(sign) => sign * f64::INFINITY,
+    }
+}
+
+/// Computes the natural logarithm of the absolute value of the beta function.
+pub fn lbeta(a: f64, b: f64) -> f64 {
+    if a <= 0. && a == a.floor() {
+        return if a == (a as i64) as f64 {
+            lbeta_negint(a as i64, b)
+        } else {
+            f64::INFINITY
+        };
+    }
+    if b <= 0. && b == b.floor() {
+        return if b == (b as i64) as f64 {
+            lbeta_negint(b as i64, a)
+        } else {
+            f64::INFINITY
+        };
+    }
+    let (a, b) = if a.abs() < b.abs() { (b, a) } else { (a, b) };
+    if a.abs() > BETA_ASYMP_FACTOR * b.abs() && a > BETA_ASYMP_FACTOR {
+        return lbeta_asymp(a, b).0;
+    }
+    let y = a + b;
+    if y.abs() > MAXGAM || a.abs() > MAXGAM || b.abs() > MAXGAM {
+        return lgamma(a) + (lgamma(b) - lgamma(y));
+    }
+    match beta_impl(a, b) {
+        Ok((y, _)) => y.ln(),
+        Err(_) => f64::INFINITY,
+    }
+}
+
+/// Computes the binomial coefficient for real-valued arguments.
+///
+/// This function calculates the generalized binomial coefficient
+///
+/// binom(n, k) = Gamma(n + 1) / (Gamma(k + 1) * Gamma(n - k + 1)),
+///
+/// where Gamma is the gamma function.
+///
+/// # Arguments
+///
+/// * `n` - The first parameter (corresponding to 'n' in the integer case)
+/// * `k` - The second parameter (corresponding to 'k' in the integer case)
+///
+/// # Returns
+///
+/// The binomial coefficient as a `f64` value, NaN if `n` is a negative integer.
+pub fn binom(n: f64, k: f64) -> f64 {
+    if n < 0. && n == n.floor() {
+        // undefined
+        return f64::NAN;
+    }
+    let mut kx = k.floor();
+    if k == kx && (n.abs() > 1e-8 || n == 0.) {
+        // integer case: use multiplication formula for less rounding error for cases
+        // where the result is an integer, this cannot be used for small nonzero n due
+        // to loss of precision.
+        let nx = n.floor();
+        if nx == n && kx > nx / 2. && nx > 0. {
+            // reduce kx by symmetry
+            kx = nx - kx;
+        }
+        if (0. ..20.).contains(&kx) {
+            let (mut num, mut den) = (1., 1.);
+            for i in 1..=(kx as i64) {
+                num *= i as f64 + n - kx;
+                den *= i as f64;
+                if num.abs() > 1e50 {
+                    num /= den;
+                    den = 1.;
+                }
+            }
+            return num / den;
+        }
+    }
+    // general case
+    if n >= 1e10 * k && k > 0. {
+        // avoid under/overflows in intermediate results
+        return (-lbeta(1. + n - k, 1. + k) - (n + 1.).ln()).exp();
+    }
+    if k > 1e8 * n.abs() {
+        // avoid loss of precision
+        let g = gamma(1. + n);
+        let num = (g / k.abs() + g * n / (2. * k * k)) / (PI * k.abs().powf(n));
+        let kx = k.floor();
+        let is_int = (kx as i64) as f64 == kx;
+        return if k > 0. {
+            let (dk, sgn) = if is_int {
+                (k - kx, if (kx as i64) % 2 == 0 { 1. } else { -1. })
+            } else {
+                (k, 1.)
+            };
+            num * ((dk - n) * PI).sin() * sgn
+        } else if is_int {
+            0.
+        } else {
+            num * (k * PI).sin()
+        };
+    }
+    1. / (n + 1.) / beta(1. + n - k, 1. + k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_close;
+
+    const EPS: f64 = 1e-14;
+
+    #[test]
+    fn test_gamma() {
+        assert_eq!(gamma(1.), 1.);
+        assert_eq!(gamma(5.), 24.);
+        assert_close(gamma(0.5), PI.sqrt(), EPS);
+        assert_close(gamma(-1.5), 4. * PI.sqrt() / 3., EPS);
+        assert_close(gamma(40.5), 1.2860502482549915e+47, EPS);
+        assert_close(gamma(-40.5), -6.0316608221787075e-49, 1e-13);
+        assert_eq!(gamma(-2.), f64::INFINITY);
+        assert_eq!(gamma(180.), f64::INFINITY);
+        assert_close(lgamma(0.5), PI.sqrt().ln(), EPS);
+        assert_close(lgamma(100.), 359.13420536957539878, EPS);
+        assert_close(lgamma(-40.5), -111.02964715732848, EPS);
+        assert_close(lgamma(1e5), 1051287.7089736569, EPS);
+        assert_eq!(gammasgn(-0.5), -1.);
+        assert_eq!(gammasgn(-1.5), 1.);
+        assert_eq!(gammasgn(2.), 1.);
+    }
+
+    #[test]
+    fn test_beta() {
+        assert_close(beta(2., 3.), 1. / 12., EPS);
+        assert_close(beta(0.5, 0.5), PI, EPS);
+        assert_close(beta(-0.5, 2.), -4., EPS);
+        assert_close(lbeta(200., 300.), -337.98011306546467, EPS);
+        assert_close(lbeta(1e7, 0.5), -7.48668287005446, EPS);
+    }
+
+    #[test]
+    fn test_binom() {
+        assert_eq!(binom(5., 2.), 10.);
+        assert_eq!(binom(50., 48.), 1225.);
+        assert!(binom(-2., 1.).is_nan());
+        let res = binom(2.2, 3.1);
+        assert!((res - 0.03739998336513408).abs() <= EPS);
+        let res = binom(2.2, 3.4);
+        assert!((res - -0.04108154623173803).abs() <= EPS);
+        assert_eq!(binom(0.5, 600.), -1.9206126162302755e-5);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::gamma::{lbeta, lgamma};
+
+/// The smallest representable number which is used to avoid division by zero in the
+/// continued fraction expansions.
+const FPMIN: f64 = f64::MIN_POSITIVE / f64::EPSILON;
+const MAX_ITER: usize = 10_000;
+/// The maximum number of Halley iterations used by the inverse functions.
+const MAX_HALLEY_ITER: usize = 20;
+
+/// `x^a * e^(-x) / Gamma(a)`, the common factor of the series and continued fraction
+/// expansions of the regularized incomplete gamma functions.
+#[inline]
+fn gamma_prefactor(a: f64, x: f64) -> f64 {
+    (a * x.ln() - x - lgamma(a)).exp()
+}
+
+/// Regularized lower incomplete gamma function by its series expansion,
+/// converges quickly for `x < a + 1`.
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let (mut ap, mut del) = (a, 1. / a);
+    let mut sum = del;
+    for _ in 0..MAX_ITER {
+        ap += 1.;
+        del *= x / ap;
+        sum += del;
+        if del.abs() < sum.abs() * f64::EPSILON {
+            break;
+        }
+    }
+    sum * gamma_prefactor(a, x)
+}
+
+/// Regularized upper incomplete gamma function by its continued fraction expansion
+/// (modified Lentz's method), converges quickly for `x >= a + 1`.
+fn gamma_cf(a: f64, x: f64) -> f64 {
+    let mut b = x + 1. - a;
+    let mut c = 1. / FPMIN;
+    let mut d = 1. / b;
+    let mut h = d;
+    for i in 1..MAX_ITER {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.;
+        d = an * d + b;
+        if d.abs() < FPMIN {
+            d = FPMIN;
+        }
+        c = b + an / c;
+        if c.abs() < FPMIN {
+            c = FPMIN;
+        }
+        d = 1. / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.).abs() <= f64::EPSILON {
+            break;
+        }
+    }
+    gamma_prefactor(a, x) * h
+}
+
+/// Computes the regularized lower incomplete gamma function `P(a, x)`.
+///
+/// # Arguments
+///
+/// * `a` - The shape parameter, must be positive.
+/// * `x` - The upper limit of integration, must be non-negative.
+///
+/// # Returns
+///
+/// `P(a, x)` in `[0, 1]`, NaN if the arguments are out of domain.
+pub fn gammainc(a: f64, x: f64) -> f64 {
+    if a.is_nan() || x.is_nan() || a <= 0. || x < 0. {
+        f64::NAN
+    } else if x == 0. {
+        0.
+    } else if x.is_infinite() {
+        1.
+    } else if x < a + 1. {
+        gamma_series(a, x)
+    } else {
+        1. - gamma_cf(a, x)
+    }
+}
+
+/// Computes the regularized upper incomplete gamma function `Q(a, x) = 1 - P(a, x)`.
+///
+/// The upper tail is computed directly so that it keeps full relative precision when
+/// it is close to zero.
+pub fn gammaincc(a: f64, x: f64) -> f64 {
+    if a.is_nan() || x.is_nan() || a <= 0. || x < 0. {
+        f64::NAN
+    } else if x == 0. {
+        1.
+    } else if x.is_infinite() {
+        0.
+    } else if x < a + 1. {
+        1. - gamma_series(a, x)
+    } else {
+        gamma_cf(a, x)
+    }
+}
+
+/// Computes the inverse of the regularized lower incomplete gamma function, i.e. the `x`
+/// such that `P(a, x) = p`.
+///
+/// # Arguments
+///
+/// * `a` - The shape parameter, must be positive.
+/// * `p` - The probability in `[0, 1]`.
+pub fn gammaincinv(a: f64, p: f64) -> f64 {
+    if a.is_nan() || p.is_nan() || a <= 0. || !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+    if p == 0. {
+        return 0.;
+    }
+    if p == 1. {
+        return f64::INFINITY;
+    }
+    let a1 = a - 1.;
+    let gln = lgamma(a);
+    let (lna1, afac) = if a > 1. {
+        let lna1 = a1.ln();
+        (lna1, (a1 * (lna1 - 1.) - gln).exp())
+    } else {
+        (0., 0.)
+    };
+    // initial guess
+    let mut x = if a > 1. {
+        let pp = if p < 0.5 { p } else { 1. - p };
+        let t = (-2. * pp.ln()).sqrt();
+        let mut x = (2.30753 + t * 0.27061) / (1. + t * (0.99229 + t * 0.04481)) - t;
+        if p < 0.5 {
+            x = -x;
+        }
+        (a * (1. - 1. / (9. * a) - x / (3. * a.sqrt())).powi(3)).max(1e-3)
+    } else {
+        let t = 1. - a * (0.253 + a * 0.12);
+        if p < t {
+            (p / t).powf(1. / a)
+        } else {
+            1. - (1. - (p - t) / (1. - t)).ln()
+        }
+    };
+    // refine by Halley's method
+    for _ in 0..MAX_HALLEY_ITER {
+        if x <= 0. {
+            return 0.;
+        }
+        let err = gammainc(a, x) - p;
+        let t = if a > 1. {
+            afac * (-(x - a1) + a1 * (x.ln() - lna1)).exp()
+        } else {
+            (-x + a1 * x.ln() - gln).exp()
+        };
+        let u = err / t;
+        let step = u / (1. - 0.5 * (u * (a1 / x - 1.)).min(1.));
+        x -= step;
+        if x <= 0. {
+            x = 0.5 * (x + step);
+        }
+        if step.abs() < f64::EPSILON * x {
+            break;
+        }
+    }
+    x
+}
+
+/// Continued fraction expansion of the regularized incomplete beta function
+/// (modified Lentz's method).
+fn beta_cf(a: f64, b: f64, x: f64) -> f64 {
+    let (qab, qap, qam) = (a + b, a + 1., a - 1.);
+    let mut c = 1.;
+    let mut d = 1. - qab * x / qap;
+    if d.abs() < FPMIN {
+        d = FPMIN;
+    }
+    d = 1. / d;
+    let mut h = d;
+    for m in 1..MAX_ITER {
+        let m = m as f64;
+        let m2 = 2. * m;
+        // even step of the recurrence
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1. + aa * d;
+        if d.abs() < FPMIN {
+            d = FPMIN;
+        }
+        c = 1. + aa / c;
+        if c.abs() < FPMIN {
+            c = FPMIN;
+        }
+        d = 1. / d;
+        h *= d * c;
+        // odd step of the recurrence
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1. + aa * d;
+        if d.abs() < FPMIN {
+            d = FPMIN;
+        }
+        c = 1. + aa / c;
+        if c.abs() < FPMIN {
+            c = FPMIN;
+        }
+        d = 1. / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.).abs() <= f64::EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// Computes the regularized incomplete beta function `I_x(a, b)`.
+///
+/// # Arguments
+///
+/// * `a` - The first shape parameter, must be positive.
+/// * `b` - The second shape parameter, must be positive.
+/// * `x` - The upper limit of integration in `[0, 1]`.
+///
+/// # Returns
+///
+/// `I_x(a, b)` in `[0, 1]`, NaN if the arguments are out of domain.
+pub fn betainc(a: f64, b: f64, x: f64) -> f64 {
+    if a.is_nan() || b.is_nan() || x.is_nan() || a <= 0. || b <= 0. || !(0. ..=1.).contains(&x) {
+        return f64::NAN;
+    }
+    if x == 0. || x == 1. {
+        return x;
+    }
+    let bt = (a * x.ln() + b * (-x).ln_1p() - lbeta(a, b)).exp();
+    // use the continued fraction directly where it converges quickly,
+    // otherwise use the symmetry relation I_x(a, b) = 1 - I_{1-x}(b, a)
+    if x < (a + 1.) / (a + b + 2.) {
+        bt * beta_cf(a, b, x) / a
+    } else {
+        1. - bt * beta_cf(b, a, 1. - x) / b
+    }
+}
+
+/// Computes the inverse of the regularized incomplete beta function, i.e. the `x`
+/// such that `I_x(a, b) = p`.
+///
+/// # Arguments
+///
+/// * `a` - The first shape parameter, must be positive.
+/// * `b` - The second shape parameter, must be positive.
+/// * `p` - The probability in `[0, 1]`.
+pub fn betaincinv(a: f64, b: f64, p: f64) -> f64 {
+    if a.is_nan() || b.is_nan() || p.is_nan() || a <= 0. || b <= 0. || !(0. ..=1.).contains(&p) {
+        return f64::NAN;
+    }
+    if p == 0. || p == 1. {
+        return p;
+    }
+    let (a1, b1) = (a - 1., b - 1.);
+    // initial guess
+    let mut x = if a >= 1. && b >= 1. {
+        let pp = if p < 0.5 { p } else { 1. - p };
+        let t = (-2. * pp.ln()).sqrt();
+        let mut x = (2.30753 + t * 0.27061) / (1. + t * (0.99229 + t * 0.04481)) - t;
+        if p < 0.5 {
+            x = -x;
+        }
+        let al = (x * x - 3.) / 6.;
+        let h = 2. / (1. / (2. * a - 1.) + 1. / (2. * b - 1.));
+        let w = (x * (al + h).sqrt() / h)
+            - (1. / (2. * b - 1.) - 1. / (2. * a - 1.)) * (al + 5. / 6. - 2. / (3. * h));
+        a / (a + b * (2. * w).exp())
+    } else {
+        let lna = (a / (a + b)).ln();
+        let lnb = (b / (a + b)).ln();
+        let t = (a * lna).exp() / a;
+        let u = (b * lnb).exp() / b;
+        let w = t + u;
+        if p < t / w {
+            (a * w * p).powf(1. / a)
+        } else {
+            1. - (b * w * (1. - p)).powf(1. / b)
+        }
+    };
+    // refine by Halley's method
+    let afac = -lbeta(a, b);
+    for i in 0..MAX_HALLEY_ITER {
+        if x == 0. || x == 1. {
+            return x;
+        }
+        let err = betainc(a, b, x) - p;
+        let t = (a1 * x.ln() + b1 * (-x).ln_1p() + afac).exp();
+        let u = err / t;
+        let step = u / (1. - 0.5 * (u * (a1 / x - b1 / (1. - x))).min(1.));
+        x -= step;
+        if x <= 0. {
+            x = 0.5 * (x + step);
+        }
+        if x >= 1. {
+            x = 0.5 * (x + step + 1.);
+        }
+        if step.abs() < f64::EPSILON * x && i > 0 {
+            break;
+        }
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_close;
+
+    #[test]
+    fn test_gammainc() {
+        assert_close(gammainc(1., 2.), 1. - (-2f64).exp(), 1e-14);
+        assert_close(gammainc(3., 2.5), 0.45618688411667048, 1e-13);
+        assert_close(gammaincc(3., 2.5), 0.54381311588332952, 1e-13);
+        assert_close(gammaincc(5., 60.), 5.0600460658425739e-21, 1e-12);
+        assert_close(gammainc(500., 480.), 0.18628197319032460, 1e-11);
+        assert!(gammainc(-1., 1.).is_nan());
+        assert_eq!(gammainc(2., 0.), 0.);
+        assert_eq!(gammaincc(2., f64::INFINITY), 0.);
+        for &(a, p) in &[
+            (0.3, 0.01),
+            (0.5, 0.5),
+            (3., 0.95),
+            (50., 0.2),
+            (500., 0.999),
+        ] {
+            let x = gammaincinv(a, p);
+            assert_close(gammainc(a, x), p, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_betainc() {
+        assert_close(betainc(1., 1., 0.3), 0.3, 1e-14);
+        assert_close(betainc(2., 3., 0.4), 0.5248, 1e-13);
+        assert_close(betainc(0.5, 10., 0.01), 0.34207182484321553, 1e-12);
+        assert_close(betainc(200., 300., 0.45), 0.98814634562026288, 1e-11);
+        assert!(betainc(1., 1., 1.5).is_nan());
+        for &(a, b, p) in &[
+            (0.5, 0.5, 0.1),
+            (2., 3., 0.5),
+            (0.5, 15., 0.9),
+            (50., 20., 0.01),
+        ] {
+            let x = betaincinv(a, b, p);
+            assert_close(betainc(a, b, x), p, 1e-12);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Pure-Rust special functions and common distributions used by tevec.
+//!
+//! - the gamma, beta and binomial coefficient functions
+//! - the error function, its complement and their inverses
+//! - the regularized incomplete gamma / beta functions and their inverses
+//! - the cdf, survival and percent point functions of the normal, Student's t and
+//!   chi-squared distributions, and the pdf of the normal distribution
+//!
+//! The gamma, beta and binomial coefficient functions are ports of the cephes
+//! implementations used by scipy, the incomplete gamma / beta functions are evaluated
+//! with series and continued fraction expansions, and the inverse functions are refined
+//! with Halley's method.
+// keep the cephes coefficients as they are published
+#![allow(clippy::excessive_precision)]
+
+mod dist;
+mod erf;
+mod gamma;
+mod incomplete;
+
+pub use dist::*;
+pub use erf::{erf, erfc, erfcinv, erfinv};
+pub use gamma::{beta, binom, gamma, gammasgn, lbeta, lgamma};
+pub use incomplete::{betainc, betaincinv, gammainc, gammaincc, gammaincinv};
+
+#[cfg(test)]
+pub(crate) fn assert_close(res: f64, expect: f64, rtol: f64) {
+    assert!(
+        (res - expect).abs() <= rtol * expect.abs(),
+        "{res} != {expect}"
+    );
+}
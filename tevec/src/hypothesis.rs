@@ -0,0 +1,623 @@
+use tea_core::prelude::*;
+use tea_special::{chi2_sf, norm_cdf, t_sf};
+
+use crate::linalg::lstsq;
+
+/// The result of a hypothesis test.
+#[derive(Clone, Copy, Debug)]
+pub struct TestResult {
+    /// The test statistic.
+    pub statistic: f64,
+    /// The p-value of the test.
+    pub p_value: f64,
+    /// The degrees of freedom of the distribution of the statistic, None if the
+    /// distribution has no degrees of freedom.
+    pub df: Option<f64>,
+    /// The number of valid observations used by the test, the sum of the sizes of both
+    /// samples for the two-sample tests.
+    pub nobs: usize,
+}
+
+/// The result of the Augmented Dickey-Fuller test.
+#[derive(Clone, Copy, Debug)]
+pub struct AdfResult {
+    /// The t statistic of the coefficient of the lagged level.
+    pub statistic: f64,
+    /// The MacKinnon approximate p-value of the test.
+    pub p_value: f64,
+    /// The residual degrees of freedom of the test regression.
+    pub df: f64,
+    /// The number of lagged differences used in the test regression.
+    pub lags: usize,
+    /// The number of observations used in the test regression.
+    pub nobs: usize,
+}
+
+/// Enum representing how the number of lagged differences of the Augmented Dickey-Fuller
+/// test is chosen.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum AdfLag {
+    /// Choose the number of lags up to the maximum which minimizes the Akaike information
+    /// criterion (default).
+    #[default]
+    Aic,
+    /// Choose the number of lags up to the maximum which minimizes the Bayesian
+    /// information criterion.
+    Bic,
+    /// Use the maximum number of lags.
+    Fixed,
+}
+
+impl TestResult {
+    #[inline]
+    fn new(statistic: f64, p_value: f64, df: Option<f64>, nobs: usize) -> Self {
+        TestResult {
+            statistic,
+            p_value,
+            df,
+            nobs,
+        }
+    }
+}
+
+/// Collects the valid values of the vector as f64.
+fn valid_f64<T: IsNone, V: Vec1View<T> + ?Sized>(vec: &V) -> Vec<f64>
+where
+    T::Inner: Number,
+{
+    vec.titer()
+        .filter_map(|v| v.to_opt().map(|v| v.f64()))
+        .collect()
+}
+
+/// Returns the mean and the unbiased variance of the values.
+fn mean_var(x: &[f64]) -> (f64, f64) {
+    let n = x.len().f64();
+    let mean = x.iter().fold(0., |acc, v| acc + v) / n;
+    let ss = x.iter().fold(0., |acc, v| acc + (v - mean).powi(2));
+    (mean, ss / (n - 1.))
+}
+
+/// Returns the two sided p-value of a t statistic.
+#[inline]
+fn t_pvalue(t: f64, df: f64) -> f64 {
+    // `clamp` keeps NaN, unlike `min`
+    (2. * t_sf(t.abs(), df)).clamp(0., 1.)
+}
+
+/// Survival function of the Kolmogorov distribution.
+fn kolmogorov_sf(x: f64) -> f64 {
+    if x.is_nan() {
+        f64::NAN
+    } else if x <= 0. {
+        1.
+    } else if x < 1.18 {
+        // the series of the cdf converges fast for small x
+        let k = -std::f64::consts::PI.powi(2) / (8. * x * x);
+        let cdf = (1..=5).fold(0., |acc, j| acc + (k * (2 * j - 1_i32).pow(2).f64()).exp())
+            * (2. * std::f64::consts::PI).sqrt()
+            / x;
+        1. - cdf
+    } else {
+        let sf = (1..=5).fold(0., |acc, j| {
+            let sign = if j % 2 == 1 { 1. } else { -1. };
+            acc + sign * (-2. * (j * j).f64() * x * x).exp()
+        });
+        (2. * sf).clamp(0., 1.)
+    }
+}
+
+/// Kolmogorov-Smirnov p-value of the statistic `d` with effective sample size `en`, using
+/// the asymptotic distribution with Stephens' correction.
+#[inline]
+fn ks_pvalue(d: f64, en: f64) -> f64 {
+    let sqrt_en = en.sqrt();
+    kolmogorov_sf((sqrt_en + 0.12 + 0.11 / sqrt_en) * d)
+}
+
+/// MacKinnon (1994) approximate p-value of the Augmented Dickey-Fuller statistic of the
+/// regression with a constant.
+fn mackinnon_pvalue(tau: f64) -> f64 {
+    const TAU_MAX: f64 = 2.74;
+    const TAU_MIN: f64 = -18.83;
+    const TAU_STAR: f64 = -1.61;
+    if tau.is_nan() {
+        f64::NAN
+    } else if tau > TAU_MAX {
+        1.
+    } else if tau < TAU_MIN {
+        0.
+    } else if tau <= TAU_STAR {
+        norm_cdf(2.1659 + tau * (1.4412 + tau * 0.038269))
+    } else {
+        norm_cdf(1.7339 + tau * (0.93202 + tau * (-0.12745 + tau * -0.010368)))
+    }
+}
+
+/// Fits the Augmented Dickey-Fuller regression of `dy[t]` on a constant, `x[t]` and
+/// `dy[t - 1], ..., dy[t - lags]` for `t` from `start`, returns the t statistic of the
+/// coefficient of `x[t]`, the residual sum of squares and the number of observations.
+fn adf_regression(x: &[f64], dy: &[f64], lags: usize, start: usize) -> (f64, f64, usize) {
+    let y = &dy[start..];
+    let nobs = y.len();
+    let mut columns = vec![vec![1.; nobs], x[start..dy.len()].to_vec()];
+    columns.extend((1..=lags).map(|k| dy[start - k..dy.len() - k].to_vec()));
+    let coef = lstsq(&columns, y);
+    let residual = |y: &[f64], columns: &[Vec<f64>], coef: &[f64]| -> f64 {
+        y.iter()
+            .enumerate()
+            .map(|(i, y)| {
+                let fit = columns
+                    .iter()
+                    .zip(coef)
+                    .fold(0., |acc, (c, b)| acc + c[i] * b);
+                (y - fit).powi(2)
+            })
+            .fold(0., |acc, r| acc + r)
+    };
+    let rss = residual(y, &columns, &coef);
+    // the variance of the coefficient is `sigma^2 / rss_x`, where `rss_x` is the residual
+    // sum of squares of the lagged level regressed on the other regressors
+    let others: Vec<_> = columns
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, c)| c.clone())
+        .collect();
+    let rss_x = residual(&columns[1], &others, &lstsq(&others, &columns[1]));
+    let sigma2 = rss / (nobs - columns.len()).f64();
+    (coef[1] / (sigma2 / rss_x).sqrt(), rss, nobs)
+}
+
+/// Trait for hypothesis tests on vectors, None values are skipped and the p-values of the
+/// tests are two sided.
+pub trait HypothesisTest<T: IsNone>: Vec1View<T> {
+    /// Performs the one-sample t-test of the null hypothesis that the mean of the data is
+    /// `mu`.
+    ///
+    /// # Returns
+    ///
+    /// The t statistic with `n - 1` degrees of freedom, the statistic and the p-value are
+    /// NaN if there are less than 2 valid values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    ///
+    /// let data = vec![Some(5.1), Some(4.9), None, Some(5.6), Some(5.8), Some(6.0)];
+    /// let res = data.vttest_1samp(5.);
+    /// assert_eq!((res.df, res.nobs), (Some(4.), 5));
+    /// assert!(res.statistic > 0. && res.p_value > 0.05);
+    /// ```
+    fn vttest_1samp(&self, mu: f64) -> TestResult
+    where
+        T::Inner: Number,
+    {
+        let x = valid_f64(self);
+        let n = x.len().f64();
+        let (mean, var) = mean_var(&x);
+        let t = (mean - mu) / (var / n).sqrt();
+        TestResult::new(t, t_pvalue(t, n - 1.), Some(n - 1.), x.len())
+    }
+
+    /// Performs the two-sample t-test of the null hypothesis that two independent samples
+    /// with equal variances have the same mean.
+    ///
+    /// # Returns
+    ///
+    /// The t statistic with `n1 + n2 - 2` degrees of freedom, see
+    /// [`vwelch_ttest`](HypothesisTest::vwelch_ttest) if the variances are not equal.
+    fn vttest_ind<V2: Vec1View<T2>, T2: IsNone>(&self, other: &V2) -> TestResult
+    where
+        T::Inner: Number,
+        T2::Inner: Number,
+    {
+        let (x, y) = (valid_f64(self), valid_f64(other));
+        let (n1, n2) = (x.len().f64(), y.len().f64());
+        let ((m1, v1), (m2, v2)) = (mean_var(&x), mean_var(&y));
+        let df = n1 + n2 - 2.;
+        let pooled = ((n1 - 1.) * v1 + (n2 - 1.) * v2) / df;
+        let t = (m1 - m2) / (pooled * (1. / n1 + 1. / n2)).sqrt();
+        TestResult::new(t, t_pvalue(t, df), Some(df), x.len() + y.len())
+    }
+
+    /// Performs Welch's t-test of the null hypothesis that two independent samples have
+    /// the same mean, the variances of the samples are not assumed to be equal.
+    ///
+    /// # Returns
+    ///
+    /// The t statistic with the Welch-Satterthwaite degrees of freedom.
+    fn vwelch_ttest<V2: Vec1View<T2>, T2: IsNone>(&self, other: &V2) -> TestResult
+    where
+        T::Inner: Number,
+        T2::Inner: Number,
+    {
+        let (x, y) = (valid_f64(self), valid_f64(other));
+        let (n1, n2) = (x.len().f64(), y.len().f64());
+        let ((m1, v1), (m2, v2)) = (mean_var(&x), mean_var(&y));
+        let (s1, s2) = (v1 / n1, v2 / n2);
+        let t = (m1 - m2) / (s1 + s2).sqrt();
+        let df = (s1 + s2).powi(2) / (s1.powi(2) / (n1 - 1.) + s2.powi(2) / (n2 - 1.));
+        TestResult::new(t, t_pvalue(t, df), Some(df), x.len() + y.len())
+    }
+
+    /// Performs the paired t-test of the null hypothesis that the mean of the differences
+    /// between two related samples is zero, the pairs with any None value are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lengths of the samples don't match.
+    fn vttest_rel<V2: Vec1View<T2>, T2: IsNone>(&self, other: &V2) -> TResult<TestResult>
+    where
+        T::Inner: Number,
+        T2::Inner: Number,
+    {
+        tensure!(self.len() == other.len(), lm, self.len(), other.len());
+        let diff: Vec<_> = self
+            .titer()
+            .zip(other.titer())
+            .map(|(a, b)| match (a.to_opt(), b.to_opt()) {
+                (Some(a), Some(b)) => a.f64() - b.f64(),
+                _ => f64::NAN,
+            })
+            .collect();
+        Ok(diff.vttest_1samp(0.))
+    }
+
+    /// Performs the Jarque-Bera test of the null hypothesis that the data is normally
+    /// distributed, using the biased sample skewness and excess kurtosis.
+    ///
+    /// # Returns
+    ///
+    /// The statistic `n / 6 * (S^2 + K^2 / 4)` with 2 degrees of freedom.
+    fn vjarque_bera(&self) -> TestResult
+    where
+        T::Inner: Number,
+    {
+        let x = valid_f64(self);
+        let n = x.len().f64();
+        let mean = x.iter().fold(0., |acc, v| acc + v) / n;
+        let (m2, m3, m4) = x.iter().fold((0., 0., 0.), |(m2, m3, m4), v| {
+            let d = v - mean;
+            let d2 = d * d;
+            (m2 + d2, m3 + d2 * d, m4 + d2 * d2)
+        });
+        let (m2, m3, m4) = (m2 / n, m3 / n, m4 / n);
+        let skew = m3 / m2.powf(1.5);
+        let kurt = m4 / m2.powi(2) - 3.;
+        let jb = n / 6. * (skew.powi(2) + kurt.powi(2) / 4.);
+        TestResult::new(jb, chi2_sf(jb, 2.), Some(2.), x.len())
+    }
+
+    /// Performs the one-sample Kolmogorov-Smirnov test of the null hypothesis that the data
+    /// is drawn from the distribution with the cumulative distribution function `cdf`.
+    ///
+    /// # Returns
+    ///
+    /// The statistic `sup |F_n(x) - cdf(x)|` with the asymptotic p-value (with Stephens'
+    /// correction), the statistic has no degrees of freedom.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    /// use tevec::special::norm_cdf;
+    ///
+    /// let data = vec![-1.2, 0.3, f64::NAN, -0.5, 0.9, 1.6, -0.1];
+    /// let res = data.vks_1samp(norm_cdf);
+    /// assert_eq!((res.df, res.nobs), (None, 6));
+    /// assert!(res.p_value > 0.5);
+    /// ```
+    fn vks_1samp<F: Fn(f64) -> f64>(&self, cdf: F) -> TestResult
+    where
+        T::Inner: Number,
+    {
+        let mut x = valid_f64(self);
+        <[_]>::sort_unstable_by(&mut x, |a, b| a.partial_cmp(b).unwrap());
+        let n = x.len().f64();
+        let d = x.iter().enumerate().fold(f64::NAN, |d: f64, (i, v)| {
+            let f = cdf(*v);
+            d.max((i + 1).f64() / n - f).max(f - i.f64() / n)
+        });
+        TestResult::new(d, ks_pvalue(d, n), None, x.len())
+    }
+
+    /// Performs the two-sample Kolmogorov-Smirnov test of the null hypothesis that two
+    /// independent samples are drawn from the same distribution.
+    ///
+    /// # Returns
+    ///
+    /// The statistic `sup |F_1(x) - F_2(x)|` with the asymptotic p-value (with Stephens'
+    /// correction) computed with the effective sample size `n1 * n2 / (n1 + n2)`, the
+    /// statistic has no degrees of freedom.
+    fn vks_2samp<V2: Vec1View<T2>, T2: IsNone>(&self, other: &V2) -> TestResult
+    where
+        T::Inner: Number,
+        T2::Inner: Number,
+    {
+        let (mut x, mut y) = (valid_f64(self), valid_f64(other));
+        <[_]>::sort_unstable_by(&mut x, |a, b| a.partial_cmp(b).unwrap());
+        <[_]>::sort_unstable_by(&mut y, |a, b| a.partial_cmp(b).unwrap());
+        let (n1, n2) = (x.len(), y.len());
+        if n1 == 0 || n2 == 0 {
+            return TestResult::new(f64::NAN, f64::NAN, None, n1 + n2);
+        }
+        let (mut i, mut j, mut d) = (0, 0, 0_f64);
+        while i < n1 && j < n2 {
+            // step both empirical distributions past the smaller value and its ties
+            let v = x[i].min(y[j]);
+            while i < n1 && x[i] <= v {
+                i += 1;
+            }
+            while j < n2 && y[j] <= v {
+                j += 1;
+            }
+            d = d.max((i.f64() / n1.f64() - j.f64() / n2.f64()).abs());
+        }
+        let en = (n1 * n2).f64() / (n1 + n2).f64();
+        TestResult::new(d, ks_pvalue(d, en), None, n1 + n2)
+    }
+
+    /// Performs the Ljung-Box test of the null hypothesis that the data has no
+    /// autocorrelation up to lag `lags`, the valid values are treated as consecutive
+    /// observations.
+    ///
+    /// # Returns
+    ///
+    /// The statistic `n * (n + 2) * sum(r_k^2 / (n - k))` with `lags` degrees of freedom.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `lags` is zero or not less than the number of valid values.
+    fn vljung_box(&self, lags: usize) -> TResult<TestResult>
+    where
+        T::Inner: Number,
+    {
+        let x = valid_f64(self);
+        let n = x.len();
+        tensure!(
+            lags > 0 && lags < n,
+            "lags must be positive and less than the number of valid values {}, find {}",
+            n,
+            lags
+        );
+        let mean = x.iter().fold(0., |acc, v| acc + v) / n.f64();
+        let d: Vec<f64> = x.iter().map(|v| v - mean).collect();
+        let c0 = d.iter().fold(0., |acc, v| acc + v * v);
+        let q = (1..=lags).fold(0., |acc, k| {
+            let ck = d[k..].iter().zip(&d).fold(0., |acc, (a, b)| acc + a * b);
+            acc + (ck / c0).powi(2) / (n - k).f64()
+        }) * (n * (n + 2)).f64();
+        Ok(TestResult::new(
+            q,
+            chi2_sf(q, lags.f64()),
+            Some(lags.f64()),
+            n,
+        ))
+    }
+
+    /// Performs the Augmented Dickey-Fuller test of the null hypothesis that the data has
+    /// a unit root, the test regression includes a constant.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_lags` - The maximum number of lagged differences, defaults to
+    ///   `ceil(12 * (n / 100)^(1 / 4))` capped at `n / 2 - 2`.
+    /// * `lag` - How the number of lags is chosen, see [`AdfLag`]. When the number of lags
+    ///   is chosen by an information criterion, all of the candidate regressions use the
+    ///   same observations and the chosen one is refitted on all available observations.
+    ///
+    /// # Returns
+    ///
+    /// The t statistic of the lagged level with the MacKinnon approximate p-value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are not enough valid values for the regression with
+    /// `max_lags` lags.
+    fn vadf(&self, max_lags: Option<usize>, lag: AdfLag) -> TResult<AdfResult>
+    where
+        T::Inner: Number,
+    {
+        let x = valid_f64(self);
+        let n = x.len();
+        let max_lags = max_lags.unwrap_or_else(|| {
+            let lags = (12. * (n.f64() / 100.).powf(0.25)).ceil().usize();
+            lags.min((n / 2).saturating_sub(2))
+        });
+        // the regression needs more observations than its max_lags + 2 parameters
+        tensure!(
+            n > 2 * max_lags + 3,
+            "not enough valid values ({}) for the Augmented Dickey-Fuller test with {} lags",
+            n,
+            max_lags
+        );
+        let dy: Vec<f64> = x.windows(2).map(|w| w[1] - w[0]).collect();
+        let lags = match lag {
+            AdfLag::Fixed => max_lags,
+            AdfLag::Aic | AdfLag::Bic => {
+                let nobs = (dy.len() - max_lags).f64();
+                let ic = |lags: usize| {
+                    let (_, rss, _) = adf_regression(&x, &dy, lags, max_lags);
+                    let llf =
+                        -nobs / 2. * ((2. * std::f64::consts::PI).ln() + (rss / nobs).ln() + 1.);
+                    let k = (lags + 2).f64();
+                    match lag {
+                        AdfLag::Aic => -2. * llf + 2. * k,
+                        _ => -2. * llf + k * nobs.ln(),
+                    }
+                };
+                (0..=max_lags)
+                    .map(|lags| (lags, ic(lags)))
+                    .fold((0, f64::INFINITY), |best, (lags, ic)| {
+                        if ic < best.1 { (lags, ic) } else { best }
+                    })
+                    .0
+            },
+        };
+        let (statistic, _, nobs) = adf_regression(&x, &dy, lags, lags);
+        Ok(AdfResult {
+            statistic,
+            p_value: mackinnon_pvalue(statistic),
+            df: (nobs - lags - 2).f64(),
+            lags,
+            nobs,
+        })
+    }
+}
+
+impl<V: Vec1View<T>, T: IsNone> HypothesisTest<T> for V {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(res: f64, expect: f64) {
+        assert!(
+            (res - expect).abs() <= 1e-9 * expect.abs().max(1.),
+            "{res} != {expect}"
+        );
+    }
+
+    #[test]
+    fn test_ttest() -> TResult<()> {
+        let a = vec![
+            Some(5.1),
+            Some(4.9),
+            None,
+            Some(5.6),
+            Some(5.8),
+            Some(6.0),
+            Some(5.3),
+            Some(4.7),
+            Some(5.5),
+        ];
+        let b = vec![4.2, 4.8, 5.0, f64::NAN, 4.4, 4.9, 4.1, 4.6];
+        let res = a.vttest_1samp(5.);
+        assert_close(res.statistic, 2.293675494561534);
+        assert_close(res.p_value, 0.05550602704909653);
+        assert_eq!((res.df, res.nobs), (Some(7.), 8));
+        let res = a.vttest_ind(&b);
+        assert_close(res.statistic, 3.773433457186089);
+        assert_close(res.p_value, 0.0023216436091593106);
+        assert_eq!((res.df, res.nobs), (Some(13.), 15));
+        let res = a.vwelch_ttest(&b);
+        assert_close(res.statistic, 3.839036743654957);
+        assert_close(res.p_value, 0.0020851414241356216);
+        assert_close(res.df.unwrap(), 12.871588365130394);
+        let res = a[..7]
+            .to_vec()
+            .vttest_rel(&vec![4.2, 4.8, 5.0, 4.4, 4.9, f64::NAN, 4.6])?;
+        assert_close(res.statistic, 4.146139914483859);
+        assert_close(res.p_value, 0.01430501787995293);
+        assert_eq!((res.df, res.nobs), (Some(4.), 5));
+        assert!(a.vttest_rel(&b).is_err());
+        assert!(vec![1.].vttest_1samp(0.).p_value.is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn test_distribution() {
+        let x = vec![
+            1.2,
+            0.4,
+            -0.3,
+            2.5,
+            0.1,
+            f64::NAN,
+            -1.1,
+            0.8,
+            3.9,
+            0.2,
+            -0.5,
+        ];
+        let y = vec![
+            Some(0.5),
+            Some(1.9),
+            Some(2.2),
+            Some(3.1),
+            None,
+            Some(-0.2),
+            Some(1.4),
+            Some(2.8),
+            Some(4.4),
+            Some(1.0),
+        ];
+        let res = x.vjarque_bera();
+        assert_close(res.statistic, 1.6812936267744671);
+        assert_close(res.p_value, 0.43143137757048744);
+        assert_eq!((res.df, res.nobs), (Some(2.), 10));
+        let res = x.vks_1samp(tea_special::norm_cdf);
+        assert_close(res.statistic, 0.239827837277029);
+        assert_close(res.p_value, 0.5514419180554115);
+        assert_eq!((res.df, res.nobs), (None, 10));
+        let res = x.vks_2samp(&y);
+        assert_close(res.statistic, 0.4888888888888889);
+        assert_close(res.p_value, 0.14365902214021908);
+        assert_eq!((res.df, res.nobs), (None, 19));
+        assert_eq!(x.vks_2samp(&x).statistic, 0.);
+    }
+
+    #[test]
+    fn test_ljung_box() -> TResult<()> {
+        let x = vec![
+            1.2,
+            0.4,
+            -0.3,
+            2.5,
+            0.1,
+            f64::NAN,
+            -1.1,
+            0.8,
+            3.9,
+            0.2,
+            -0.5,
+        ];
+        let res = x.vljung_box(3)?;
+        assert_close(res.statistic, 7.048975491618829);
+        assert_close(res.p_value, 0.07035303172952659);
+        assert_eq!((res.df, res.nobs), (Some(3.), 10));
+        assert!(x.vljung_box(0).is_err());
+        assert!(x.vljung_box(10).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_adf() -> TResult<()> {
+        // a random walk and a stationary AR(1) process driven by the same uniform noise
+        let mut state: u64 = 42;
+        let (mut rw, mut ar) = (vec![0.], vec![0.]);
+        for _ in 1..120 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let e = (state >> 11) as f64 / (1_u64 << 53) as f64 - 0.5;
+            rw.push(rw[rw.len() - 1] + e);
+            ar.push(0.2 * ar[ar.len() - 1] + e);
+        }
+        let res = rw.vadf(Some(4), AdfLag::Fixed)?;
+        assert_close(res.statistic, -2.7391416910670663);
+        assert_close(res.p_value, 0.06750647808035752);
+        assert_eq!((res.df, res.lags, res.nobs), (109., 4, 115));
+        // the test is invariant to the scale of the data
+        for scale in [1e-4, 1e-8, 1e-12] {
+            let scaled: Vec<f64> = rw.iter().map(|v| v * scale).collect();
+            let res = scaled.vadf(Some(4), AdfLag::Fixed)?;
+            assert!((res.statistic + 2.7391416910670663).abs() < 1e-6);
+            assert!((res.p_value - 0.06750647808035752).abs() < 1e-6);
+        }
+        let res = rw.vadf(Some(4), AdfLag::Aic)?;
+        assert_close(res.statistic, -2.349223032139871);
+        assert_close(res.p_value, 0.1565833958990592);
+        assert_eq!((res.lags, res.nobs), (0, 119));
+        let res = rw.vadf(Some(4), AdfLag::Bic)?;
+        assert_eq!(res.lags, 0);
+        let res = ar.vadf(None, AdfLag::Aic)?;
+        assert_close(res.statistic, -9.17437763847847);
+        assert!(res.p_value < 1e-10);
+        assert_eq!(res.lags, 0);
+        assert!(rw[..10].to_vec().vadf(Some(4), AdfLag::Fixed).is_err());
+        Ok(())
+    }
+}
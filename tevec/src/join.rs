@@ -0,0 +1,320 @@
+use std::ops::Sub;
+
+use tea_core::prelude::*;
+use tea_map::{MapValidVec, SearchSide};
+
+/// Enum representing which observation of the right series is matched by an asof join.
+#[derive(Default, Clone, Copy, Debug)]
+pub enum AsofDirection {
+    /// The last observation at or before the left time (default).
+    #[default]
+    Backward,
+    /// The first observation at or after the left time.
+    Forward,
+    /// The nearest observation, the backward one is used if both are equally near.
+    Nearest,
+}
+
+/// Trait for aligning series with different sorted `DateTime` indices, the vector
+/// implementing this trait is the left index.
+pub trait TimeAlign<U: TimeUnitTrait>: Vec1View<DateTime<U>> {
+    /// Aligns the right values to the left index by matching each left time with an
+    /// observation of the right index, e.g. the latest quote of each trade.
+    ///
+    /// Both indices are expected to be sorted in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `right_index` - The time of each right value.
+    /// * `right_values` - The values to align.
+    /// * `direction` - Which observation is matched, see [`AsofDirection`].
+    /// * `tolerance` - The maximum time between the left time and the matched observation.
+    /// * `allow_exact_matches` - If false, observations at the same time as the left time
+    ///   are not matched.
+    ///
+    /// # Returns
+    ///
+    /// The right values aligned to the left index, None if there is no match or the
+    /// left time is NaT.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lengths of `right_index` and `right_values` don't match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    /// use tevec::join::{AsofDirection, TimeAlign};
+    ///
+    /// let dt = |s: i64| DateTime::<unit::Nanosecond>::new(s * 1_000_000_000);
+    /// let trades = vec![dt(1), dt(5), dt(10)];
+    /// let quotes = vec![dt(0), dt(5), dt(8)];
+    /// let bids = vec![Some(1.), Some(2.), Some(3.)];
+    /// let res: Vec<_> = trades
+    ///     .asof_join(&quotes, &bids, AsofDirection::Backward, None, true)
+    ///     .unwrap();
+    /// assert_eq!(res, vec![Some(1.), Some(2.), Some(3.)]);
+    /// let res: Vec<_> = trades
+    ///     .asof_join(&quotes, &bids, AsofDirection::Backward, None, false)
+    ///     .unwrap();
+    /// assert_eq!(res, vec![Some(1.), Some(1.), Some(3.)]);
+    /// ```
+    fn asof_join<O: Vec1<T>, V2, V3, T>(
+        &self,
+        right_index: &V2,
+        right_values: &V3,
+        direction: AsofDirection,
+        tolerance: Option<TimeDelta>,
+        allow_exact_matches: bool,
+    ) -> TResult<O>
+    where
+        Self: Sized,
+        V2: Vec1View<DateTime<U>>,
+        V3: Vec1View<T>,
+        T: IsNone,
+        DateTime<U>: Sub<Output = TimeDelta>,
+    {
+        let n_right = right_index.len();
+        tensure!(
+            right_values.len() == n_right,
+            lm,
+            n_right,
+            right_values.len()
+        );
+        // the first right observation at or after (after) each left time
+        let (back_side, forward_side) = if allow_exact_matches {
+            (SearchSide::Right, SearchSide::Left)
+        } else {
+            (SearchSide::Left, SearchSide::Right)
+        };
+        let backward: Vec<usize> = right_index.vsearchsorted(self, back_side);
+        let forward: Vec<usize> = right_index.vsearchsorted(self, forward_side);
+        let within = |dist: TimeDelta| tolerance.is_none_or(|tolerance| dist <= tolerance);
+        Ok(self
+            .titer()
+            .enumerate()
+            .map(|(i, t)| {
+                if t.is_nat() {
+                    return T::none();
+                }
+                // safety: the matched positions are less than the length of the right index
+                let back = backward[i]
+                    .checked_sub(1)
+                    .map(|j| (j, t - unsafe { right_index.uget(j) }));
+                let forth = (forward[i] < n_right)
+                    .then(|| (forward[i], unsafe { right_index.uget(forward[i]) } - t));
+                let matched = match direction {
+                    AsofDirection::Backward => back,
+                    AsofDirection::Forward => forth,
+                    AsofDirection::Nearest => match (back, forth) {
+                        (Some(b), Some(f)) => Some(if f.1 < b.1 { f } else { b }),
+                        (b, f) => b.or(f),
+                    },
+                };
+                match matched {
+                    Some((j, dist)) if within(dist) => unsafe { right_values.uget(j) },
+                    _ => T::none(),
+                }
+            })
+            .collect_trusted_vec1())
+    }
+
+    /// Aligns two series on the union of their indices, the values at the times which
+    /// are not in the index of a series are None.
+    ///
+    /// Both indices are expected to be sorted in ascending order without duplicates.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the union index, the reindexed left values and the reindexed right
+    /// values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of an index doesn't match the length of its values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    /// use tevec::join::TimeAlign;
+    ///
+    /// let dt = |s: i64| DateTime::<unit::Nanosecond>::new(s);
+    /// let left = vec![dt(1), dt(3)];
+    /// let right = vec![dt(2), dt(3), dt(4)];
+    /// let (index, l, r): (Vec<_>, Vec<_>, Vec<_>) = left
+    ///     .outer_align(&vec![Some(1), Some(3)], &right, &vec![Some(20), Some(30), Some(40)])
+    ///     .unwrap();
+    /// assert_eq!(index, vec![dt(1), dt(2), dt(3), dt(4)]);
+    /// assert_eq!(l, vec![Some(1), None, Some(3), None]);
+    /// assert_eq!(r, vec![None, Some(20), Some(30), Some(40)]);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    fn outer_align<OI, O1, O2, V2, V3, V4, T1, T2>(
+        &self,
+        left_values: &V2,
+        right_index: &V3,
+        right_values: &V4,
+    ) -> TResult<(OI, O1, O2)>
+    where
+        OI: Vec1<DateTime<U>>,
+        O1: Vec1<T1>,
+        O2: Vec1<T2>,
+        V2: Vec1View<T1>,
+        V3: Vec1View<DateTime<U>>,
+        V4: Vec1View<T2>,
+        T1: IsNone,
+        T2: IsNone,
+    {
+        let (n_left, n_right) = (self.len(), right_index.len());
+        tensure!(left_values.len() == n_left, lm, n_left, left_values.len());
+        tensure!(
+            right_values.len() == n_right,
+            lm,
+            n_right,
+            right_values.len()
+        );
+        // merge the sorted indices, each element is the union time and the positions
+        // in the left and the right index
+        let mut merged: Vec<(DateTime<U>, Option<usize>, Option<usize>)> =
+            Vec::with_capacity(n_left + n_right);
+        let (mut i, mut j) = (0, 0);
+        // safety: i and j are checked against the lengths of the indices
+        while i < n_left || j < n_right {
+            let l = (i < n_left).then(|| unsafe { self.uget(i) });
+            let r = (j < n_right).then(|| unsafe { right_index.uget(j) });
+            match (l, r) {
+                (Some(l), Some(r)) if l == r => {
+                    merged.push((l, Some(i), Some(j)));
+                    i += 1;
+                    j += 1;
+                },
+                (Some(l), Some(r)) if l < r => {
+                    merged.push((l, Some(i), None));
+                    i += 1;
+                },
+                (Some(l), None) => {
+                    merged.push((l, Some(i), None));
+                    i += 1;
+                },
+                (_, Some(r)) => {
+                    merged.push((r, None, Some(j)));
+                    j += 1;
+                },
+                (None, None) => unreachable!(),
+            }
+        }
+        let index = merged.iter().map(|m| m.0).collect_trusted_vec1();
+        let left = merged
+            .iter()
+            .map(|m| {
+                m.1.map_or_else(T1::none, |i| unsafe { left_values.uget(i) })
+            })
+            .collect_trusted_vec1();
+        let right = merged
+            .iter()
+            .map(|m| {
+                m.2.map_or_else(T2::none, |j| unsafe { right_values.uget(j) })
+            })
+            .collect_trusted_vec1();
+        Ok((index, left, right))
+    }
+}
+
+impl<U: TimeUnitTrait, V: Vec1View<DateTime<U>>> TimeAlign<U> for V {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(secs: &[i64]) -> Vec<DateTime> {
+        secs.iter()
+            .map(|s| DateTime::new(s * 1_000_000_000))
+            .collect()
+    }
+
+    #[test]
+    fn test_asof_join() -> TResult<()> {
+        let left = index(&[0, 2, 5, 9, 20]);
+        let right = index(&[1, 2, 6, 10]);
+        let values = vec![Some(1), Some(2), Some(6), Some(10)];
+        let join = |direction, tolerance, exact| -> TResult<Vec<Option<i32>>> {
+            left.asof_join(&right, &values, direction, tolerance, exact)
+        };
+        use AsofDirection::*;
+        assert_eq!(
+            join(Backward, None, true)?,
+            vec![None, Some(2), Some(2), Some(6), Some(10)]
+        );
+        assert_eq!(
+            join(Backward, None, false)?,
+            vec![None, Some(1), Some(2), Some(6), Some(10)]
+        );
+        assert_eq!(
+            join(Forward, None, true)?,
+            vec![Some(1), Some(2), Some(6), Some(10), None]
+        );
+        assert_eq!(
+            join(Forward, None, false)?,
+            vec![Some(1), Some(6), Some(6), Some(10), None]
+        );
+        assert_eq!(
+            join(Nearest, None, true)?,
+            vec![Some(1), Some(2), Some(6), Some(10), Some(10)]
+        );
+        let tolerance = Some(TimeDelta::parse("1s")?);
+        assert_eq!(
+            join(Nearest, tolerance, true)?,
+            vec![Some(1), Some(2), Some(6), Some(10), None]
+        );
+        assert_eq!(
+            join(Backward, tolerance, true)?,
+            vec![None, Some(2), None, None, None]
+        );
+        // a tie of the nearest observations uses the backward one
+        let left = index(&[4]);
+        let res: Vec<Option<i32>> = left.asof_join(&right, &values, Nearest, None, true)?;
+        assert_eq!(res, vec![Some(2)]);
+        let left = vec![DateTime::nat()];
+        let res: Vec<Option<i32>> = left.asof_join(&right, &values, Backward, None, true)?;
+        assert_eq!(res, vec![None]);
+        let res: TResult<Vec<Option<i32>>> =
+            left.asof_join(&right, &values[..2].to_vec(), Backward, None, true);
+        assert!(res.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_outer_align() -> TResult<()> {
+        let left = index(&[1, 3, 5]);
+        let right = index(&[0, 3, 6, 7]);
+        let (idx, l, r): (Vec<_>, Vec<_>, Vec<_>) = left.outer_align(
+            &vec![1., 3., 5.],
+            &right,
+            &vec![Some(0), Some(3), Some(6), Some(7)],
+        )?;
+        assert_eq!(idx, index(&[0, 1, 3, 5, 6, 7]));
+        assert_eq!(
+            l.iter().map(|v| v.is_none()).collect::<Vec<_>>(),
+            vec![true, false, false, false, true, true]
+        );
+        assert_eq!(l[1..4], [1., 3., 5.]);
+        assert_eq!(r, vec![Some(0), None, Some(3), None, Some(6), Some(7)]);
+        let empty: Vec<DateTime> = vec![];
+        let (idx, l, r): (Vec<_>, Vec<f64>, Vec<_>) =
+            empty.outer_align(&Vec::<f64>::new(), &right, &vec![1, 2, 3, 4])?;
+        assert_eq!(idx, right);
+        assert_eq!(l.len(), 4);
+        assert_eq!(r, vec![1, 2, 3, 4]);
+        assert!(
+            left.outer_align::<Vec<_>, Vec<_>, Vec<_>, _, _, _, _, _>(
+                &vec![1.],
+                &right,
+                &vec![1; 4]
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+}
@@ -68,7 +68,7 @@
 //!
 //! **rolling**: Rolling Functions
 //!
-//! **stat**: Statistic Functions
+//! **stat**: Statistic Functions (pure-Rust special functions, distributions and hypothesis tests)
 //!
 //! **time**: `DateTime` and `TimeDelta` structs
 
@@ -83,7 +83,15 @@ pub extern crate tea_macros as macros;
 
 #[cfg(feature = "agg")]
 pub mod agg;
+#[cfg(feature = "stat")]
+pub mod hypothesis;
+#[cfg(all(feature = "time", feature = "map"))]
+pub mod join;
+#[cfg(any(feature = "map", feature = "stat"))]
+mod linalg;
 #[cfg(feature = "map")]
 pub mod map;
 #[cfg(feature = "rolling")]
 pub mod rolling;
+#[cfg(feature = "stat")]
+pub use tea_special as special;
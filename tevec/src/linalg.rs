@@ -0,0 +1,57 @@
+/// Fits a multiple ordinary least squares regression of `y` on the given columns by
+/// solving the normal equations.
+///
+/// A column which is (nearly) a linear combination of the previous columns gets a zero
+/// coefficient, so the fitted values and the residuals are still the least squares ones
+/// when the columns are collinear, e.g. an intercept together with all of the dummy
+/// columns of a categorical variable.
+///
+/// # Arguments
+///
+/// * `columns` - The regressors, each of them has the same length as `y`. An intercept
+///   is only fitted if one of the columns is constant.
+/// * `y` - The dependent variable.
+///
+/// # Returns
+///
+/// The coefficient of each column.
+pub(crate) fn lstsq(columns: &[Vec<f64>], y: &[f64]) -> Vec<f64> {
+    let k = columns.len();
+    // the augmented matrix [X'X | X'y]
+    let mut a: Vec<Vec<f64>> = (0..k)
+        .map(|i| {
+            let mut row: Vec<f64> = (0..k).map(|j| dot(&columns[i], &columns[j])).collect();
+            row.push(dot(&columns[i], y));
+            row
+        })
+        .collect();
+    let diag: Vec<f64> = (0..k).map(|i| a[i][i]).collect();
+    let mut kept = vec![false; k];
+    // Gauss-Jordan elimination, X'X is positive semi-definite so that a vanishing pivot
+    // means the whole row and column vanish and the column can be dropped, the tolerance
+    // is relative to the column's own sum of squares so the solver is scale invariant
+    for i in 0..k {
+        let pivot = a[i][i];
+        if pivot <= 1e-10 * diag[i].max(f64::MIN_POSITIVE) {
+            continue;
+        }
+        kept[i] = true;
+        let pivot_row = a[i].clone();
+        for (r, row) in a.iter_mut().enumerate() {
+            let f = row[i] / pivot;
+            if r != i && f != 0. {
+                for (v, p) in row[i..].iter_mut().zip(&pivot_row[i..]) {
+                    *v -= f * p;
+                }
+            }
+        }
+    }
+    (0..k)
+        .map(|i| if kept[i] { a[i][k] / a[i][i] } else { 0. })
+        .collect()
+}
+
+#[inline]
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).fold(0., |acc, (x, y)| acc + x * y)
+}
@@ -2,6 +2,9 @@
 use tea_agg::*;
 use tea_core::prelude::*;
 pub use tea_map::*;
+
+use crate::linalg::lstsq;
+
 /// Enum representing different methods for winsorizing data.
 #[cfg(feature = "agg")]
 #[derive(Copy, Clone)]
@@ -12,6 +15,75 @@ pub enum WinsorizeMethod {
     Median,
     /// Winsorize based on standard deviations from the mean.
     Sigma,
+    /// Winsorize based on standard deviations from the mean iteratively, the mean and
+    /// the standard deviation are recomputed from the winsorized data until the
+    /// bounds converge.
+    SigmaIter,
+}
+
+/// Enum representing what winsorizing does with the values outside of the bounds.
+#[cfg(feature = "agg")]
+#[derive(Default, Copy, Clone)]
+pub enum WinsorizeMode {
+    /// Clip the values to the bounds (default).
+    #[default]
+    Clip,
+    /// Replace the values with NaN.
+    Nan,
+}
+
+/// Enum representing different methods for standardizing data.
+#[cfg(feature = "agg")]
+#[derive(Copy, Clone)]
+pub enum StandardizeMethod {
+    /// Subtract the mean and divide by the standard deviation.
+    ZScore,
+    /// Subtract the median and divide by the scaled median absolute deviation
+    /// (`1.4826 * MAD`, consistent with the standard deviation of normal data).
+    Robust,
+    /// Average ranks linearly mapped to `[-1, 1]`.
+    Rank,
+    /// Linearly map the minimum to 0 and the maximum to 1.
+    MinMax,
+}
+
+/// The maximum number of iterations of [`WinsorizeMethod::SigmaIter`].
+#[cfg(feature = "agg")]
+const SIGMA_ITER_MAX: usize = 100;
+
+/// Returns the bounds `mean ± n_sigma * std`, None if the standard deviation is zero or
+/// can't be computed.
+#[cfg(feature = "agg")]
+fn sigma_bounds<T: IsNone, I: TrustedLen<Item = T>>(iter: I, n_sigma: f64) -> Option<(f64, f64)>
+where
+    T::Inner: Number,
+{
+    let (mean, var) = iter.vmean_var(2);
+    if mean.not_none() && var.not_none() && var > EPS {
+        let std = var.sqrt();
+        Some((mean - n_sigma * std, mean + n_sigma * std))
+    } else {
+        None
+    }
+}
+
+/// Returns the median and the median absolute deviation, the median is NaN if there is
+/// no valid value.
+#[cfg(feature = "agg")]
+fn median_mad<V: VecAggValidExt<T> + ?Sized, T: IsNone + Cast<f64>>(vec: &V) -> (f64, f64)
+where
+    T::Inner: Number,
+{
+    let median = vec.vmedian();
+    if median.not_none() {
+        let mad = vec
+            .map(|v| (v.cast() - median).abs())
+            .collect_trusted_to_vec()
+            .vmedian();
+        (median, mad)
+    } else {
+        (f64::NAN, f64::NAN)
+    }
 }
 
 /// Trait for performing mapping operations on vectors with valid (non-None) elements.
@@ -40,66 +112,300 @@ pub trait MapValidFinal<T: IsNone>: Vec1View<T> {
     /// let winsorized: Vec<f64> = data.winsorize(WinsorizeMethod::Quantile, Some(0.1)).unwrap().collect();
     /// ```
     #[cfg(feature = "agg")]
+    #[inline]
     fn winsorize<'a>(
         &'a self,
         method: WinsorizeMethod,
         method_params: Option<f64>,
     ) -> TResult<Box<dyn TrustedLen<Item = f64> + 'a>>
+    where
+        T: Cast<f64> + 'a,
+        T::Inner: Number,
+        Self: VecAggValidExt<T>,
+    {
+        self.winsorize_with(method, method_params, WinsorizeMode::Clip)
+    }
+
+    /// Winsorizes the data using the specified method and mode.
+    ///
+    /// See [`winsorize`](MapValidFinal::winsorize) for the methods, the parameter of
+    /// [`WinsorizeMethod::SigmaIter`] is the number of standard deviations (default: 3).
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The winsorization method to use.
+    /// * `method_params` - Optional parameter specific to the chosen method.
+    /// * `mode` - Whether the values outside of the bounds are clipped or replaced with NaN.
+    ///
+    /// # Returns
+    ///
+    /// A `TResult` containing a boxed iterator of winsorized values as `f64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    /// use tevec::map::{WinsorizeMethod, WinsorizeMode};
+    ///
+    /// let data = vec![1., 2., 3., 4., 100.];
+    /// let res: Vec<f64> = data
+    ///     .winsorize_with(WinsorizeMethod::Median, Some(3.), WinsorizeMode::Nan)
+    ///     .unwrap()
+    ///     .collect();
+    /// assert_eq!(res[..4], [1., 2., 3., 4.]);
+    /// assert!(res[4].is_nan());
+    /// ```
+    #[cfg(feature = "agg")]
+    fn winsorize_with<'a>(
+        &'a self,
+        method: WinsorizeMethod,
+        method_params: Option<f64>,
+        mode: WinsorizeMode,
+    ) -> TResult<Box<dyn TrustedLen<Item = f64> + 'a>>
     where
         T: Cast<f64> + 'a,
         T::Inner: Number,
         Self: VecAggValidExt<T>,
     {
         use WinsorizeMethod::*;
-        match method {
+        let bounds = match method {
             Quantile => {
                 use tea_agg::QuantileMethod;
                 let method_params = method_params.unwrap_or(0.01);
-                let min = self.vquantile(method_params, QuantileMethod::Linear)?;
-                let max = self.vquantile(1. - method_params, QuantileMethod::Linear)?;
-                Ok(Box::new(self.iter_cast::<f64>().vclip(min, max)))
+                let bounds =
+                    self.vquantiles(&[method_params, 1. - method_params], QuantileMethod::Linear)?;
+                Some((bounds[0], bounds[1]))
             },
             Median => {
                 // default method is clip median - 3 * mad, median + 3 * mad
                 let method_params = method_params.unwrap_or(3.);
-                let median = self.vmedian();
-                if median.not_none() {
-                    let mad = self
-                        .map(|v| (v.cast() - median).abs())
-                        .collect_trusted_to_vec()
-                        .vmedian();
-                    let min = median - method_params * mad;
-                    let max = median + method_params * mad;
-                    Ok(Box::new(self.iter_cast::<f64>().vclip(min, max)))
-                } else {
-                    Ok(Box::new(self.iter_cast::<f64>()))
-                }
+                let (median, mad) = median_mad(self);
+                median
+                    .not_none()
+                    .then_some((median - method_params * mad, median + method_params * mad))
             },
             Sigma => {
                 // default method is clip mean - 3 * std, mean + 3 * std
+                sigma_bounds(self.titer(), method_params.unwrap_or(3.))
+            },
+            SigmaIter => {
                 let method_params = method_params.unwrap_or(3.);
-                let (mean, var) = self.titer().vmean_var(2);
-                if mean.not_none() && var.not_none() && var > EPS {
-                    let std = var.sqrt();
-                    let min = mean - method_params * std;
-                    let max = mean + method_params * std;
-                    Ok(Box::new(self.iter_cast::<f64>().vclip(min, max)))
-                } else {
-                    Ok(Box::new(self.iter_cast::<f64>()))
+                let mut data: Vec<f64> = self.iter_cast::<f64>().collect_trusted_to_vec();
+                let mut last_bounds: Option<(f64, f64)> = None;
+                for _ in 0..SIGMA_ITER_MAX {
+                    let Some((min, max)) = sigma_bounds(data.titer(), method_params) else {
+                        break;
+                    };
+                    if let Some((last_min, last_max)) = last_bounds {
+                        let tol = EPS * (1. + min.abs().max(max.abs()));
+                        if (min - last_min).abs() <= tol && (max - last_max).abs() <= tol {
+                            break;
+                        }
+                    }
+                    let mut changed = false;
+                    for v in data.iter_mut() {
+                        if *v < min || *v > max {
+                            changed = true;
+                            *v = match mode {
+                                WinsorizeMode::Clip => v.clamp(min, max),
+                                WinsorizeMode::Nan => f64::NAN,
+                            };
+                        }
+                    }
+                    if !changed {
+                        break;
+                    }
+                    last_bounds = Some((min, max));
                 }
+                return Ok(Box::new(data.into_iter()));
+            },
+        };
+        match (bounds, mode) {
+            (None, _) => Ok(Box::new(self.iter_cast::<f64>())),
+            (Some((min, max)), WinsorizeMode::Clip) => {
+                Ok(Box::new(self.iter_cast::<f64>().vclip(min, max)))
+            },
+            (Some((min, max)), WinsorizeMode::Nan) => {
+                Ok(Box::new(self.iter_cast::<f64>().map(move |v| {
+                    if v < min || v > max { f64::NAN } else { v }
+                })))
             },
         }
     }
+
+    /// Standardizes the data using the specified method, None values are NaN in the
+    /// output.
+    ///
+    /// The output is all NaN if the scale of the data (the standard deviation, the
+    /// median absolute deviation or the range) is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The standardization method to use.
+    ///
+    /// # Returns
+    ///
+    /// A `TResult` containing a boxed iterator of standardized values as `f64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    /// use tevec::map::StandardizeMethod;
+    ///
+    /// let data = vec![1, 2, 3, 5];
+    /// let res: Vec<f64> = data.standardize(StandardizeMethod::MinMax).unwrap().collect();
+    /// assert_eq!(res, vec![0., 0.25, 0.5, 1.]);
+    /// ```
+    #[cfg(feature = "agg")]
+    fn standardize<'a>(
+        &'a self,
+        method: StandardizeMethod,
+    ) -> TResult<Box<dyn TrustedLen<Item = f64> + 'a>>
+    where
+        T: Cast<f64> + PartialEq + 'a,
+        T::Inner: Number,
+        Self: VecAggValidExt<T> + Sized,
+    {
+        use StandardizeMethod::*;
+        let (center, scale) = match method {
+            ZScore => {
+                let (mean, var) = self.titer().vmean_var(2);
+                (mean, var.sqrt())
+            },
+            Robust => {
+                let (median, mad) = median_mad(self);
+                (median, 1.4826 * mad)
+            },
+            MinMax => match (self.titer().vmin(), self.titer().vmax()) {
+                (Some(min), Some(max)) => (min.f64(), max.f64() - min.f64()),
+                _ => (f64::NAN, f64::NAN),
+            },
+            Rank => {
+                let res: Vec<f64> = self.vrank_with(
+                    RankMethod::Average,
+                    NanPlacement::Keep,
+                    RankNorm::Signed,
+                    false,
+                );
+                return Ok(Box::new(res.into_iter()));
+            },
+        };
+        if scale.is_nan() || scale <= EPS {
+            return Ok(Box::new(std::iter::repeat_n(f64::NAN, self.len())));
+        }
+        Ok(Box::new(
+            self.iter_cast::<f64>().map(move |v| (v - center) / scale),
+        ))
+    }
+
+    /// Neutralizes the data against the exposures by returning the residuals of an
+    /// ordinary least squares regression of the data on the exposures, an intercept and
+    /// the dummy columns of the categorical variable (e.g. the industries).
+    ///
+    /// An element whose data, exposures or category is None is not used in the
+    /// regression and its residual is NaN.
+    ///
+    /// # Arguments
+    ///
+    /// * `exposures` - The exposure vectors, e.g. the size and the beta of the stocks.
+    /// * `categorical` - An optional categorical vector, e.g. the industry of the stocks.
+    ///
+    /// # Returns
+    ///
+    /// A `TResult` containing a boxed iterator of the residuals.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length of an exposure or the categorical vector doesn't
+    /// match the length of the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tevec::prelude::*;
+    /// use tevec::map::MapValidFinal;
+    ///
+    /// let factor = vec![3., 5., 7., 4., 6.];
+    /// let size = vec![1., 2., 3., 1., 2.];
+    /// let industry = vec!["a", "a", "a", "b", "b"];
+    /// let resid: Vec<f64> = factor.vneutralize(&[&size], Some(&industry)).unwrap().collect();
+    /// assert!(resid.iter().fold(0., |acc, r| acc + r.abs()) < 1e-10);
+    /// ```
+    fn vneutralize<'a, V2, T2, V3, T3>(
+        &'a self,
+        exposures: &[&V2],
+        categorical: Option<&V3>,
+    ) -> TResult<Box<dyn TrustedLen<Item = f64> + 'a>>
+    where
+        T: Cast<f64>,
+        V2: Vec1View<T2>,
+        T2: IsNone + Cast<f64>,
+        V3: Vec1View<T3>,
+        T3: IsNone,
+        T3::Inner: HashKey,
+    {
+        let len = self.len();
+        let to_f64 = |v: T2| if v.not_none() { v.cast() } else { f64::NAN };
+        let mut x: Vec<Vec<f64>> = Vec::with_capacity(exposures.len());
+        for exposure in exposures {
+            tensure!(exposure.len() == len, lm, len, exposure.len());
+            x.push(exposure.titer().map(to_f64).collect_trusted_to_vec());
+        }
+        let (codes, n_categories): (Vec<Option<i32>>, usize) = match categorical {
+            Some(categorical) => {
+                tensure!(categorical.len() == len, lm, len, categorical.len());
+                let (codes, uniques) = categorical.titer().vfactorize();
+                (codes, uniques.len())
+            },
+            None => (vec![Some(0); len], 0),
+        };
+        let y: Vec<f64> = self
+            .titer()
+            .map(|v| if v.not_none() { v.cast() } else { f64::NAN })
+            .collect_trusted_to_vec();
+        let rows: Vec<usize> = (0..len)
+            .filter(|i| {
+                if y[*i].is_nan() || codes[*i].is_none() {
+                    return false;
+                }
+                for col in &x {
+                    if col[*i].is_nan() {
+                        return false;
+                    }
+                }
+                true
+            })
+            .collect();
+        // the intercept, the exposures and the dummy columns of the valid rows, the
+        // collinearity of the intercept and the dummy columns is handled by `lstsq`
+        let mut columns: Vec<Vec<f64>> = vec![vec![1.; rows.len()]];
+        columns.extend(x.iter().map(|col| rows.iter().map(|i| col[*i]).collect()));
+        columns.extend((0..n_categories).map(|c| {
+            rows.iter()
+                .map(|i| if codes[*i] == Some(c as i32) { 1. } else { 0. })
+                .collect()
+        }));
+        let y_valid: Vec<f64> = rows.iter().map(|i| y[*i]).collect();
+        let coef = lstsq(&columns, &y_valid);
+        let mut out = vec![f64::NAN; len];
+        for (j, i) in rows.iter().enumerate() {
+            let fitted = columns
+                .iter()
+                .zip(&coef)
+                .fold(0., |acc, (col, b)| acc + col[j] * b);
+            out[*i] = y_valid[j] - fitted;
+        }
+        Ok(Box::new(out.into_iter()))
+    }
 }
 
 impl<V: Vec1View<T>, T: IsNone> MapValidFinal<T> for V {}
 
 #[cfg(test)]
 mod tests {
-    #[cfg(feature = "agg")]
     use tea_core::testing::assert_vec1d_equal_numeric;
 
-    #[cfg(feature = "agg")]
     use super::*;
     #[test]
     #[cfg(feature = "agg")]
@@ -129,4 +435,133 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "agg")]
+    fn test_winsorize_with() -> TResult<()> {
+        let nan = f64::NAN;
+        let a = vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 10.];
+        let b: Vec<_> = a
+            .winsorize_with(WinsorizeMethod::Quantile, Some(0.1), WinsorizeMode::Nan)?
+            .collect();
+        assert_vec1d_equal_numeric(&b, &vec![nan, 2., 3., 4., 5., 6., 7., 8., 9., nan], None);
+        // iterative clipping converges to bounds which don't clip any more values
+        let a = vec![0., 0.1, -0.2, 0.3, -0.1, 0.2, 0., -0.3, 0.1, 50.];
+        let b: Vec<_> = a
+            .winsorize_with(WinsorizeMethod::SigmaIter, Some(2.), WinsorizeMode::Clip)?
+            .collect();
+        let (mean, var) = b.titer().vmean_var(2);
+        let max = b.titer().vmax().unwrap();
+        assert!(max < 50. && max >= mean + 2. * var.sqrt() - 1e-10);
+        assert_eq!(b[..9], a[..9]);
+        // iterative rejection removes the outliers only
+        let b: Vec<_> = a
+            .winsorize_with(WinsorizeMethod::SigmaIter, Some(2.), WinsorizeMode::Nan)?
+            .collect();
+        assert_eq!(b[..9], a[..9]);
+        assert!(b[9].is_nan());
+        // the single pass sigma clipping doesn't converge
+        let b: Vec<_> = a.winsorize(WinsorizeMethod::Sigma, Some(2.))?.collect();
+        assert!(b[9] > max);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "agg")]
+    fn test_standardize() -> TResult<()> {
+        let nan = f64::NAN;
+        let a = vec![Some(1.), Some(2.), None, Some(3.), Some(10.)];
+        let b: Vec<_> = a.standardize(StandardizeMethod::ZScore)?.collect();
+        let std = (50f64 / 3.).sqrt();
+        assert_vec1d_equal_numeric(
+            &b,
+            &vec![-3. / std, -2. / std, nan, -1. / std, 6. / std],
+            Some(1e-10),
+        );
+        let b: Vec<_> = a.standardize(StandardizeMethod::Robust)?.collect();
+        // median 2.5, mad 1
+        assert_vec1d_equal_numeric(
+            &b,
+            &vec![
+                -1.5 / 1.4826,
+                -0.5 / 1.4826,
+                nan,
+                0.5 / 1.4826,
+                7.5 / 1.4826,
+            ],
+            Some(1e-10),
+        );
+        let b: Vec<_> = a.standardize(StandardizeMethod::Rank)?.collect();
+        assert_vec1d_equal_numeric(&b, &vec![-1., -1. / 3., nan, 1. / 3., 1.], Some(1e-10));
+        let b: Vec<_> = a.standardize(StandardizeMethod::MinMax)?.collect();
+        assert_vec1d_equal_numeric(&b, &vec![0., 1. / 9., nan, 2. / 9., 1.], Some(1e-10));
+        // the range of integers is computed in f64 so it can't overflow
+        let b: Vec<_> = vec![i32::MIN, 0, i32::MAX]
+            .standardize(StandardizeMethod::MinMax)?
+            .collect();
+        assert_vec1d_equal_numeric(&b, &vec![0., 0.5, 1.], Some(1e-9));
+        let b: Vec<_> = vec![1., 1.]
+            .standardize(StandardizeMethod::ZScore)?
+            .collect();
+        assert_vec1d_equal_numeric(&b, &vec![nan, nan], None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_neutralize() -> TResult<()> {
+        let nan = f64::NAN;
+        let factor = vec![1., 4., 2., 8., nan, 5., 7., 3.];
+        let size = vec![1., 2., 3., 4., 5., nan, 2., 1.];
+        let beta = vec![0.5, 0.1, 0.9, 0.3, 0.2, 0.4, 0.8, 0.6];
+        let industry = vec![
+            Some(1),
+            Some(2),
+            Some(1),
+            Some(2),
+            Some(1),
+            Some(2),
+            None,
+            Some(1),
+        ];
+        let resid: Vec<f64> = factor
+            .vneutralize(&[&size, &beta], Some(&industry))?
+            .collect();
+        let valid = [0, 1, 2, 3, 7];
+        for i in [4, 5, 6] {
+            assert!(resid[i].is_nan());
+        }
+        // the residuals are orthogonal to the regressors
+        let dot =
+            |x: &dyn Fn(usize) -> f64| valid.iter().fold(0., |acc, i| acc + x(*i) * resid[*i]);
+        assert!(dot(&|_| 1.).abs() < 1e-10);
+        assert!(dot(&|i| size[i]).abs() < 1e-10);
+        assert!(dot(&|i| beta[i]).abs() < 1e-10);
+        assert!(dot(&|i| (industry[i] == Some(1)) as i32 as f64).abs() < 1e-10);
+        // the residuals don't depend on the scale of the exposures
+        let small_size: Vec<f64> = size.iter().map(|v| v * 1e-8).collect();
+        let small_beta: Vec<f64> = beta.iter().map(|v| v * 1e-8).collect();
+        let scaled: Vec<f64> = factor
+            .vneutralize(&[&small_size, &small_beta], Some(&industry))?
+            .collect();
+        assert_vec1d_equal_numeric(&scaled, &resid, Some(1e-8));
+        // demean without exposures and categories
+        let resid: Vec<f64> = factor
+            .vneutralize::<Vec<f64>, f64, Vec<i32>, i32>(&[], None)?
+            .collect();
+        assert_vec1d_equal_numeric(
+            &resid,
+            &factor.iter().map(|v| v - 30. / 7.).collect::<Vec<_>>(),
+            Some(1e-10),
+        );
+        // regress on size only
+        let y = vec![1., 3., 2., 6.];
+        let x = vec![0., 1., 2., 3.];
+        let resid: Vec<f64> = y.vneutralize::<_, _, Vec<i32>, i32>(&[&x], None)?.collect();
+        assert_vec1d_equal_numeric(&resid, &vec![0.1, 0.7, -1.7, 0.9], Some(1e-10));
+        assert!(
+            y.vneutralize::<_, _, Vec<i32>, i32>(&[&vec![1., 2.]], None)
+                .is_err()
+        );
+        Ok(())
+    }
 }
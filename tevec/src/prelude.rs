@@ -2,6 +2,10 @@ pub use tea_core::prelude::*;
 
 #[cfg(feature = "agg")]
 pub use super::agg::*;
+#[cfg(feature = "stat")]
+pub use super::hypothesis::*;
+#[cfg(all(feature = "time", feature = "map"))]
+pub use super::join::*;
 #[cfg(feature = "map")]
 pub use super::map::*;
 #[cfg(feature = "rolling")]
@@ -4,6 +4,8 @@ pub use tea_rolling::*;
 ///
 /// This function computes the coefficients used in fractional differencing,
 /// which is a generalization of integer differencing to non-integer orders.
+/// The coefficients are calculated with the recursive formula
+/// `w_0 = 1, w_k = -w_{k-1} * (d - k + 1) / k`, so no special function is needed.
 ///
 /// # Arguments
 ///
@@ -12,17 +14,51 @@ pub use tea_rolling::*;
 ///
 /// # Returns
 ///
-/// A vector of coefficients for fractional differencing.
+/// A vector of coefficients for fractional differencing, the coefficient of the
+/// oldest element comes first.
 #[cfg(feature = "fdiff")]
 fn fdiff_coef(d: f64, window: usize) -> Vec<f64> {
-    let mut sign = if window.is_multiple_of(2) { 1. } else { -1. };
-    (0..window)
-        .rev()
-        .map(|v| {
-            sign = -sign;
-            ffi::binom(d, v as f64) * sign
+    let mut w = 1.;
+    let mut coef: Vec<f64> = (0..window)
+        .map(|k| {
+            if k > 0 {
+                w = -w * (d - (k - 1).f64()) / k.f64();
+            }
+            w
         })
-        .collect_trusted_to_vec()
+        .collect_trusted_to_vec();
+    coef.reverse();
+    coef
+}
+
+/// Calculates the window of fixed-width window fractional differencing (FFD).
+///
+/// The window is the number of coefficients whose absolute value is not less than
+/// `threshold`, as the coefficients calculated by the recursive formula decay in
+/// absolute value for `d > -1`.
+///
+/// # Arguments
+///
+/// * `d` - The order of fractional differencing.
+/// * `threshold` - The weight-cutoff threshold, must be greater than 0.
+/// * `max_window` - The maximum size of the window.
+///
+/// # Returns
+///
+/// The size of the window, which is at least 1.
+#[cfg(feature = "fdiff")]
+pub fn ffd_window(d: f64, threshold: f64, max_window: usize) -> usize {
+    assert!(threshold > 0., "threshold must be greater than 0");
+    let mut w = 1.;
+    let mut window = 1;
+    while window < max_window {
+        w = -w * (d - (window - 1).f64()) / window.f64();
+        if w.abs() < threshold {
+            break;
+        }
+        window += 1;
+    }
+    window
 }
 
 /// Trait for performing rolling operations on vectors.
@@ -64,6 +100,51 @@ pub trait RollingFinal<T>: Vec1View<T> {
             out,
         )
     }
+
+    /// Performs fixed-width window fractional differencing (FFD) on the vector.
+    ///
+    /// Unlike [`ts_fdiff`](RollingFinal::ts_fdiff), the window is not supplied by the
+    /// user but determined by dropping the coefficients whose absolute value is less
+    /// than `threshold`, see [`ffd_window`]. The same weights are applied to every
+    /// window, so only full windows produce a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `d` - The order of fractional differencing.
+    /// * `threshold` - The weight-cutoff threshold, e.g. `1e-4`.
+    /// * `out` - An optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the fractionally differenced values, the first `window - 1`
+    /// values are NaN.
+    #[cfg(feature = "fdiff")]
+    #[no_out]
+    fn ts_ffd<O: Vec1<U>, U: Clone>(
+        &self,
+        d: f64,
+        threshold: f64,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T: Cast<f64>,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        f64: Cast<U>,
+    {
+        let window = ffd_window(d, threshold, self.len().max(1));
+        let coef = fdiff_coef(d, window);
+        self.rolling_custom(
+            window,
+            |arr| {
+                if arr.len() < window {
+                    return f64::NAN.cast();
+                }
+                let acc_func = |acc: f64, (v, c): (T, f64)| acc + v.cast() * c;
+                arr.titer().zip(coef.titer()).fold(0., acc_func).cast()
+            },
+            out,
+        )
+    }
 }
 
 /// Trait for performing rolling operations on vectors with valid (non-None) elements.
@@ -125,6 +206,87 @@ pub trait RollingValidFinal<T: IsNone>: Vec1View<T> {
             out,
         )
     }
+
+    /// Performs fixed-width window fractional differencing (FFD) on the vector, handling None values.
+    ///
+    /// The window is determined by dropping the coefficients whose absolute value is less
+    /// than `threshold`, see [`ffd_window`]. The same weights are applied to every window,
+    /// so only full windows without None values produce a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `d` - The order of fractional differencing.
+    /// * `threshold` - The weight-cutoff threshold, e.g. `1e-4`.
+    /// * `out` - An optional output buffer to store the results.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the fractionally differenced values, with NaN for the first
+    /// `window - 1` values and the windows containing None values.
+    #[cfg(feature = "fdiff")]
+    #[no_out]
+    fn ts_vffd<O: Vec1<U>, U: Clone>(
+        &self,
+        d: f64,
+        threshold: f64,
+        out: Option<O::UninitRefMut<'_>>,
+    ) -> O
+    where
+        T::Inner: Number,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        f64: Cast<U>,
+    {
+        let window = ffd_window(d, threshold, self.len().max(1));
+        self.ts_vfdiff_to(d, window, Some(window), out)
+    }
+
+    /// Searches the minimum order `d` of fixed-width window fractional differencing
+    /// which makes the series stationary.
+    ///
+    /// The orders `d_min, d_min + step, ...` not greater than `d_max` are tried in turn,
+    /// and the differenced series (full windows only) is passed to `is_stationary`.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The weight-cutoff threshold used by [`ts_vffd`](RollingValidFinal::ts_vffd).
+    /// * `d_min` - The smallest order to try.
+    /// * `d_max` - The largest order to try.
+    /// * `step` - The step between two tried orders, must be greater than 0.
+    /// * `is_stationary` - The stationarity criterion, e.g. an ADF test whose p-value is below 5%.
+    ///
+    /// # Returns
+    ///
+    /// The minimum order which passes the stationarity criterion, or `None` if no order passes.
+    #[cfg(feature = "fdiff")]
+    fn ffd_min_d<F>(
+        &self,
+        threshold: f64,
+        d_min: f64,
+        d_max: f64,
+        step: f64,
+        mut is_stationary: F,
+    ) -> Option<f64>
+    where
+        T::Inner: Number,
+        for<'a> Self::SliceOutput<'a>: TIter<T>,
+        F: FnMut(&[f64]) -> bool,
+    {
+        assert!(step > 0., "step must be greater than 0");
+        let mut i = 0;
+        loop {
+            let d = d_min + i.f64() * step;
+            if d > d_max + EPS {
+                return None;
+            }
+            let window = ffd_window(d, threshold, self.len().max(1));
+            let res: Vec<f64> = self.ts_vfdiff(d, window, Some(window));
+            let res: Vec<f64> = res.into_iter().filter(|v| v.not_none()).collect();
+            if !res.is_empty() && is_stationary(&res) {
+                return Some(d);
+            }
+            i += 1;
+        }
+    }
 }
 
 impl<I: Vec1View<T>, T: IsNone> RollingValidFinal<T> for I {}
@@ -138,16 +300,6 @@ mod tests {
     #[cfg(feature = "fdiff")]
     use super::*;
 
-    #[cfg(feature = "fdiff")]
-    #[test]
-    fn test_binom() {
-        let res = ffi::binom(2.2, 3.1);
-        assert!((res - 0.03739998336513408).abs() <= EPS);
-        let res = ffi::binom(2.2, 3.4);
-        assert!((res - -0.04108154623173803).abs() <= EPS);
-        assert_eq!(ffi::binom(0.5, 600.), -1.9206126162302755e-5);
-    }
-
     #[cfg(feature = "fdiff")]
     #[test]
     fn test_fdiff_coef() {
@@ -161,6 +313,41 @@ mod tests {
         assert_vec1d_equal_numeric(&res, &vec![-0.0625, -0.125, -0.5, 1.], Some(EPS));
     }
 
+    #[cfg(feature = "fdiff")]
+    #[test]
+    fn test_ffd() {
+        // |w| of d = 0.5 are 1, 0.5, 0.125, 0.0625, 0.0390625, ...
+        assert_eq!(ffd_window(0.5, 0.05, 100), 4);
+        assert_eq!(ffd_window(0.5, 0.05, 3), 3);
+        // integer order gives exact zero weights
+        assert_eq!(ffd_window(1., 1e-5, 100), 2);
+        let arr = vec![7, 4, 2, 5, 1, 2];
+        let nan = f64::NAN;
+        // the window of d = 0.5 and threshold 0.05 is 4
+        let res: Vec<f64> = arr.ts_vffd(0.5, 0.05);
+        let expect = vec![nan, nan, nan, 3.0625, -2., 0.75];
+        assert_vec1d_equal_numeric(&res, &expect, Some(EPS));
+        let res: Vec<f64> = arr.ts_ffd(0.5, 0.05);
+        assert_vec1d_equal_numeric(&res, &expect, Some(EPS));
+        let res: Vec<f64> = arr.ts_ffd(1., 1e-5);
+        assert_vec1d_equal_numeric(&res, &vec![nan, -3., -2., 3., -4., 1.], Some(EPS));
+        // a window containing None values gives NaN
+        let arr = vec![Some(7.), Some(4.), None, Some(5.), Some(1.), Some(2.)];
+        let res: Vec<f64> = arr.ts_vffd(1., 1e-5);
+        assert_vec1d_equal_numeric(&res, &vec![nan, -3., nan, nan, -4., 1.], Some(EPS));
+    }
+
+    #[cfg(feature = "fdiff")]
+    #[test]
+    fn test_ffd_min_d() {
+        let arr: Vec<f64> = (0..100).map(|v| v as f64).collect();
+        // the first difference of a linear trend is constant
+        let is_constant = |v: &[f64]| v.titer().vstd(2) < 1e-6;
+        let d = arr.ffd_min_d(1e-4, 0., 1., 0.1, is_constant);
+        assert!((d.unwrap() - 1.).abs() < EPS);
+        assert_eq!(arr.ffd_min_d(1e-4, 0., 0.5, 0.1, is_constant), None);
+    }
+
     #[cfg(feature = "fdiff")]
     #[test]
     fn test_fdiff() {